@@ -0,0 +1,526 @@
+use std::sync::{Arc, OnceLock};
+
+use movies_core::sea_orm::DatabaseConnection;
+use movies_core::{FieldError, LoginUser, RegisterUser, UserResponse, Validate};
+use movies_macros::IntoResponse;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use utoipa::{IntoParams, IntoResponses, OpenApi};
+
+use crate::auth::{session_token, CurrentUser, SESSION_COOKIE};
+use crate::config::SessionConfig;
+use crate::extract::Json;
+use crate::oidc::{LoginState, OidcClient, OidcError};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(register, login, logout, get_current_user, oidc_login, oidc_callback),
+    components(schemas(
+        RegisterUser,
+        LoginUser,
+        UserResponse,
+        Problem,
+        ProblemCode,
+        FieldError
+    )),
+    tags((name = "auth", description = "User accounts and their sessions"))
+)]
+pub struct AccountsApiDocs;
+
+pub fn accounts_routes(
+    db: DatabaseConnection,
+    session: SessionConfig,
+    oidc: Option<Arc<OidcClient>>,
+) -> Router {
+    Router::new()
+        .route("/register", post(register))
+        .route("/login", post(login))
+        .route("/logout", post(logout))
+        .route("/me", get(get_current_user))
+        .route("/oidc/login", get(oidc_login))
+        .route("/oidc/callback", get(oidc_callback))
+        .with_state(AccountsState { db, session, oidc })
+}
+
+#[derive(Clone)]
+struct AccountsState {
+    db: DatabaseConnection,
+    session: SessionConfig,
+    oidc: Option<Arc<OidcClient>>,
+}
+
+impl FromRef<AccountsState> for DatabaseConnection {
+    fn from_ref(state: &AccountsState) -> Self {
+        state.db.clone()
+    }
+}
+
+/// Run slow password hashing off the async runtime.
+async fn hash_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    tokio::task::spawn_blocking(f)
+        .await
+        .expect("password hashing does not panic")
+}
+
+/// `Set-Cookie` header value giving the session cookie `value` for `max_age_secs`, or removing it
+/// when 0.
+fn session_cookie(value: &str, max_age_secs: u64, secure: bool) -> HeaderValue {
+    let secure = if secure { "; Secure" } else { "" };
+    let cookie = format!(
+        "{SESSION_COOKIE}={value}; Max-Age={max_age_secs}; Path=/; HttpOnly; SameSite=Lax{secure}"
+    );
+
+    HeaderValue::from_str(&cookie).expect("session tokens are alphanumeric")
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum RegisterResponses {
+    #[response(status = OK)]
+    Success(#[json] UserResponse),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Create a user account
+///
+/// Fails with a conflict if the email is already taken. Log in afterwards to get a session.
+#[utoipa::path(
+        post,
+        path = "/auth/register",
+        request_body = RegisterUser,
+        responses(RegisterResponses, DatabaseErrorResponses),
+        tag = "auth"
+    )]
+async fn register(
+    state: State<AccountsState>,
+    Json(data): Json<RegisterUser>,
+) -> Result<RegisterResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(RegisterResponses::UnprocessableEntity(Problem::validation(
+            errors,
+        )));
+    }
+
+    let RegisterUser {
+        email,
+        display_name,
+        password,
+    } = data;
+    let password_hash = hash_blocking(move || movies_core::hash_password(&password)).await;
+
+    match movies_core::create_user(&state.db, &email, display_name, password_hash).await {
+        Ok(user) => Ok(RegisterResponses::Success(user.into())),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum LoginResponses {
+    /// Logged in, with the session cookie set
+    #[response(status = OK)]
+    Success(#[json] UserResponse),
+
+    #[response(status = UNAUTHORIZED, content_type = "application/problem+json")]
+    Unauthorized(Problem),
+}
+
+/// Log in with an email and password
+///
+/// Sets an HTTP-only session cookie, which logs the user in for the next requests.
+#[utoipa::path(
+        post,
+        path = "/auth/login",
+        request_body = LoginUser,
+        responses(LoginResponses, DatabaseErrorResponses),
+        tag = "auth"
+    )]
+async fn login(
+    state: State<AccountsState>,
+    Json(data): Json<LoginUser>,
+) -> Result<Response, DatabaseErrorResponses> {
+    let user = movies_core::find_user_by_email(&state.db, &data.email).await?;
+
+    // Check the password of unknown users, and of those logging in with OpenID Connect only,
+    // against some hash all the same, so that response times do not tell which emails have a
+    // password
+    let password_hash = user.as_ref().and_then(|user| user.password_hash.clone());
+    let has_password = password_hash.is_some();
+    let password_hash = match password_hash {
+        Some(password_hash) => password_hash,
+        None => unknown_user_password_hash().await,
+    };
+    let password = data.password;
+    let verified =
+        hash_blocking(move || movies_core::verify_password(&password, &password_hash)).await;
+    let valid = has_password && verified;
+
+    let user = match user {
+        Some(user) if valid => user,
+        _ => {
+            return Ok(LoginResponses::Unauthorized(Problem::unauthorized(
+                "Wrong email or password",
+            ))
+            .into_response())
+        }
+    };
+
+    let cookie = start_session(&state, user.id).await?;
+
+    let mut response = LoginResponses::Success(user.into()).into_response();
+    response.headers_mut().insert(header::SET_COOKIE, cookie);
+
+    Ok(response)
+}
+
+/// Create a session for a user who just logged in, returning the `Set-Cookie` header value
+/// giving it to the browser.
+async fn start_session(
+    state: &AccountsState,
+    user_id: i32,
+) -> Result<HeaderValue, DatabaseErrorResponses> {
+    let ttl = state.session.ttl;
+    let session = movies_core::create_session(
+        &state.db,
+        user_id,
+        chrono::Duration::from_std(ttl).expect("the session TTL is at most a year"),
+    )
+    .await?;
+
+    Ok(session_cookie(
+        &session.token,
+        ttl.as_secs(),
+        state.session.secure_cookie,
+    ))
+}
+
+async fn unknown_user_password_hash() -> String {
+    static HASH: OnceLock<String> = OnceLock::new();
+
+    match HASH.get() {
+        Some(hash) => hash.clone(),
+        None => {
+            let hash = hash_blocking(|| movies_core::hash_password("not a password")).await;
+            HASH.get_or_init(|| hash).clone()
+        }
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum LogoutResponses {
+    /// Logged out, with the session cookie removed
+    #[response(status = NO_CONTENT)]
+    Success,
+}
+
+/// Log out, ending the current session if there is one
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    responses(LogoutResponses, DatabaseErrorResponses),
+    tag = "auth"
+)]
+async fn logout(
+    state: State<AccountsState>,
+    headers: HeaderMap,
+) -> Result<Response, DatabaseErrorResponses> {
+    if let Some(token) = session_token(&headers) {
+        movies_core::delete_session(&state.db, token).await?;
+    }
+
+    let mut response = LogoutResponses::Success.into_response();
+    response.headers_mut().insert(
+        header::SET_COOKIE,
+        session_cookie("", 0, state.session.secure_cookie),
+    );
+
+    Ok(response)
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetCurrentUserResponses {
+    #[response(status = OK)]
+    Success(#[json] UserResponse),
+}
+
+/// Get the logged in user
+#[utoipa::path(
+        get,
+        path = "/auth/me",
+        responses(GetCurrentUserResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "auth"
+    )]
+async fn get_current_user(CurrentUser(user): CurrentUser) -> GetCurrentUserResponses {
+    GetCurrentUserResponses::Success(user.into())
+}
+
+/// Cookie keeping the state of an OpenID Connect login between leaving for the provider and
+/// coming back to the callback.
+const OIDC_LOGIN_COOKIE: &str = "movies_oidc_login";
+
+/// Seconds users have to log in at the provider.
+const OIDC_LOGIN_TTL_SECS: u64 = 10 * 60;
+
+/// Content of the `OIDC_LOGIN_COOKIE`.
+#[derive(Serialize, Deserialize)]
+struct OidcLoginCookie {
+    #[serde(flatten)]
+    login: LoginState,
+    redirect_to: String,
+}
+
+fn oidc_login_cookie(value: &str, max_age_secs: u64, secure: bool) -> HeaderValue {
+    let secure = if secure { "; Secure" } else { "" };
+    let cookie = format!(
+        "{OIDC_LOGIN_COOKIE}={value}; Max-Age={max_age_secs}; Path=/auth/oidc; HttpOnly; \
+         SameSite=Lax{secure}"
+    );
+
+    HeaderValue::from_str(&cookie).expect("the login state is URL encoded")
+}
+
+fn read_oidc_login_cookie(headers: &HeaderMap) -> Option<OidcLoginCookie> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == OIDC_LOGIN_COOKIE)
+        .and_then(|(_, value)| serde_urlencoded::from_str(value).ok())
+}
+
+/// Only paths of this server are accepted, so that logins cannot be used to send users to other
+/// sites.
+fn is_local_path(path: &str) -> bool {
+    path.starts_with('/') && !path.starts_with("//") && !path.contains('\\')
+}
+
+fn oidc_not_configured() -> Problem {
+    Problem::not_found("Logging in with OpenID Connect is not configured")
+}
+
+fn identity_provider_problem(err: &OidcError) -> Problem {
+    Problem::new(
+        StatusCode::BAD_GATEWAY,
+        ProblemCode::IdentityProviderError,
+        err.to_string(),
+    )
+}
+
+/// Where to go once logged in with OpenID Connect
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct OidcLoginParams {
+    /// Path of this server to redirect to once logged in, `/` by default
+    redirect_to: Option<String>,
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum OidcLoginResponses {
+    /// Redirection to the identity provider
+    #[response(status = SEE_OTHER)]
+    Redirect,
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = BAD_GATEWAY, content_type = "application/problem+json")]
+    BadGateway(Problem),
+}
+
+/// Log in with the OpenID Connect provider
+///
+/// Redirects to the provider, which redirects back to `/auth/oidc/callback` once the user logged
+/// in there.
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/login",
+    params(OidcLoginParams),
+    responses(OidcLoginResponses),
+    tag = "auth"
+)]
+async fn oidc_login(
+    state: State<AccountsState>,
+    params: Result<Query<OidcLoginParams>, QueryRejection>,
+) -> Response {
+    let Some(oidc) = &state.oidc else {
+        return OidcLoginResponses::NotFound(oidc_not_configured()).into_response();
+    };
+
+    let redirect_to = match params {
+        Ok(Query(params)) => params.redirect_to.unwrap_or_else(|| "/".to_owned()),
+        Err(rejection) => {
+            return OidcLoginResponses::BadRequest(Problem::bad_request(rejection.body_text()))
+                .into_response()
+        }
+    };
+    if !is_local_path(&redirect_to) {
+        return OidcLoginResponses::BadRequest(Problem::bad_request(
+            "`redirect_to` must be a path of this server",
+        ))
+        .into_response();
+    }
+
+    let (url, login) = match oidc.start_login().await {
+        Ok(started) => started,
+        Err(err) => {
+            warn!("Cannot start an OpenID Connect login: {err}");
+            return OidcLoginResponses::BadGateway(identity_provider_problem(&err)).into_response();
+        }
+    };
+
+    let cookie = serde_urlencoded::to_string(OidcLoginCookie { login, redirect_to })
+        .expect("the login state is a flat struct of strings");
+
+    let mut response = OidcLoginResponses::Redirect.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::LOCATION,
+        HeaderValue::from_str(url.as_str()).expect("URLs are valid header values"),
+    );
+    headers.insert(
+        header::SET_COOKIE,
+        oidc_login_cookie(&cookie, OIDC_LOGIN_TTL_SECS, state.session.secure_cookie),
+    );
+
+    response
+}
+
+/// What the identity provider gives back to the callback
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct OidcCallbackParams {
+    /// Authorization code, when the user logged in
+    code: Option<String>,
+    /// State of the login, as given to the provider
+    state: Option<String>,
+    /// Error code, when the login failed
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum OidcCallbackResponses {
+    /// Logged in, with the session cookie set, and redirected to `redirect_to`
+    #[response(status = SEE_OTHER)]
+    Redirect,
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+
+    #[response(status = UNAUTHORIZED, content_type = "application/problem+json")]
+    Unauthorized(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = BAD_GATEWAY, content_type = "application/problem+json")]
+    BadGateway(Problem),
+}
+
+/// Finish logging in with the OpenID Connect provider
+///
+/// The provider redirects users here once they logged in. Their local account is created on their
+/// first login, or linked to an existing one with the same verified email, and their role updated
+/// from the claims of the provider if so configured. Linking removes the password of the account
+/// and logs out its sessions, as whoever registered it may not own the email.
+#[utoipa::path(
+    get,
+    path = "/auth/oidc/callback",
+    params(OidcCallbackParams),
+    responses(OidcCallbackResponses, DatabaseErrorResponses),
+    tag = "auth"
+)]
+async fn oidc_callback(
+    state: State<AccountsState>,
+    headers: HeaderMap,
+    params: Result<Query<OidcCallbackParams>, QueryRejection>,
+) -> Result<Response, DatabaseErrorResponses> {
+    let Some(oidc) = &state.oidc else {
+        return Ok(OidcCallbackResponses::NotFound(oidc_not_configured()).into_response());
+    };
+
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return Ok(OidcCallbackResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            ))
+            .into_response())
+        }
+    };
+
+    let cookie = read_oidc_login_cookie(&headers)
+        .filter(|cookie| params.state.as_deref() == Some(cookie.login.state.as_str()));
+    let Some(cookie) = cookie else {
+        return Ok(OidcCallbackResponses::BadRequest(Problem::bad_request(
+            "The login expired or was started in another browser, log in again",
+        ))
+        .into_response());
+    };
+
+    if let Some(error) = params.error {
+        let description = params.error_description.unwrap_or_default();
+        return Ok(OidcCallbackResponses::Unauthorized(Problem::unauthorized(
+            format!("The identity provider refused the login: {error} {description}").trim_end(),
+        ))
+        .into_response());
+    }
+    let Some(code) = params.code else {
+        return Ok(OidcCallbackResponses::BadRequest(Problem::bad_request(
+            "Missing `code` parameter",
+        ))
+        .into_response());
+    };
+
+    let identity = oidc
+        .finish_login(&code, &cookie.login)
+        .await
+        .and_then(|claims| oidc.identity(claims));
+    let identity = match identity {
+        Ok(identity) => identity,
+        Err(err @ OidcError::Rejected(_)) => {
+            warn!("OpenID Connect login rejected: {err}");
+            return Ok(
+                OidcCallbackResponses::Unauthorized(Problem::unauthorized(err.to_string()))
+                    .into_response(),
+            );
+        }
+        Err(err @ OidcError::Provider(_)) => {
+            warn!("Cannot finish an OpenID Connect login: {err}");
+            return Ok(
+                OidcCallbackResponses::BadGateway(identity_provider_problem(&err)).into_response(),
+            );
+        }
+    };
+
+    let user = movies_core::upsert_oidc_user(&state.db, identity).await?;
+    let session_cookie = start_session(&state, user.id).await?;
+
+    let mut response = OidcCallbackResponses::Redirect.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::LOCATION,
+        HeaderValue::from_str(&cookie.redirect_to).unwrap_or(HeaderValue::from_static("/")),
+    );
+    headers.append(header::SET_COOKIE, session_cookie);
+    headers.append(
+        header::SET_COOKIE,
+        oidc_login_cookie("", 0, state.session.secure_cookie),
+    );
+
+    Ok(response)
+}
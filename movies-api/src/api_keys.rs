@@ -0,0 +1,54 @@
+use movies_core::sea_orm::{ActiveEnum, DatabaseConnection, DbErr};
+
+pub use movies_entity::sea_orm_active_enums::ApiKeyScope;
+
+/// Management of the API keys, from the command line.
+#[derive(Debug, Clone)]
+pub enum ApiKeyCommand {
+    /// Create a key and print it, which is the only time it is shown
+    Mint { name: String, scope: ApiKeyScope },
+    /// Stop accepting a key
+    Revoke { id: i32 },
+    /// Print the keys, without the keys themselves which are not stored
+    List,
+}
+
+pub(crate) async fn run_api_key_command(
+    db: &DatabaseConnection,
+    command: ApiKeyCommand,
+) -> anyhow::Result<()> {
+    match command {
+        ApiKeyCommand::Mint { name, scope } => {
+            let created = movies_core::create_api_key(db, name, scope).await?;
+            eprintln!(
+                "Created API key {} `{}`, store it now as it cannot be shown again:",
+                created.api_key.id, created.api_key.name
+            );
+            println!("{}", created.key);
+        }
+        ApiKeyCommand::Revoke { id } => match movies_core::revoke_api_key(db, id).await {
+            Ok(api_key) => eprintln!("Revoked API key {} `{}`", api_key.id, api_key.name),
+            Err(DbErr::RecordNotFound(message)) => anyhow::bail!(message),
+            Err(err) => return Err(err.into()),
+        },
+        ApiKeyCommand::List => {
+            println!("ID\tPREFIX\tSCOPE\tCREATED\tREVOKED\tNAME");
+            for api_key in movies_core::get_all_api_keys(db).await? {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    api_key.id,
+                    api_key.prefix,
+                    api_key.scope.to_value(),
+                    api_key.created_at.to_rfc3339(),
+                    api_key
+                        .revoked_at
+                        .map(|revoked_at| revoked_at.to_rfc3339())
+                        .unwrap_or_else(|| "-".to_owned()),
+                    api_key.name,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,398 @@
+use std::marker::PhantomData;
+
+use movies_core::sea_orm::{ActiveEnum, DatabaseConnection};
+use movies_entity::sea_orm_active_enums::UserRole;
+use movies_entity::{api_key, user};
+
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use utoipa::openapi::security::{
+    ApiKey as ApiKeyIn, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme,
+};
+use utoipa::openapi::OpenApi;
+
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem};
+
+/// Name of the security scheme of the routes needing an API key in the OpenAPI docs.
+pub const SECURITY_SCHEME: &str = "api_key";
+/// Name of the security scheme of the routes needing a logged in user in the OpenAPI docs.
+pub const SESSION_SECURITY_SCHEME: &str = "session";
+
+/// Cookie holding the token of the session of a logged in user.
+pub const SESSION_COOKIE: &str = "movies_session";
+
+/// Operation guarded by the access policy, naming the least role allowed to do it.
+pub trait Operation: Send + Sync {
+    const ROLE: UserRole;
+}
+
+/// Reading movies, persons and credits, open to anonymous callers as well.
+pub struct View;
+
+impl Operation for View {
+    const ROLE: UserRole = UserRole::Viewer;
+}
+
+/// Creating and editing movies, persons and credits.
+pub struct Edit;
+
+impl Operation for Edit {
+    const ROLE: UserRole = UserRole::Editor;
+}
+
+/// Deleting movies, persons and credits.
+pub struct Delete;
+
+impl Operation for Delete {
+    const ROLE: UserRole = UserRole::Admin;
+}
+
+/// Listing users, changing their role and deleting them.
+pub struct ManageUsers;
+
+impl Operation for ManageUsers {
+    const ROLE: UserRole = UserRole::Admin;
+}
+
+/// Publishing and hiding the reviews users write.
+pub struct Moderate;
+
+impl Operation for Moderate {
+    const ROLE: UserRole = UserRole::Admin;
+}
+
+/// Who made a request, as identified by its credentials.
+#[derive(Debug, Clone)]
+pub enum Caller {
+    Anonymous,
+    /// Sent an API key as a bearer token
+    ApiKey(api_key::Model),
+    /// Sent the session cookie of a logged in user
+    User(user::Model),
+}
+
+impl Caller {
+    /// Role the caller acts with, anonymous callers being viewers.
+    pub fn role(&self) -> UserRole {
+        match self {
+            Caller::Anonymous => UserRole::Viewer,
+            Caller::ApiKey(api_key) => api_key.scope.role(),
+            Caller::User(user) => user.role,
+        }
+    }
+
+    /// Identify the caller from the API key sent as a bearer token, or else from the session
+    /// cookie, rejecting API keys that are invalid or were revoked.
+    async fn identify(headers: &HeaderMap, db: &DatabaseConnection) -> Result<Self, Response> {
+        if let Some(key) = bearer_token(headers) {
+            return match movies_core::find_api_key(db, key).await {
+                Ok(Some(api_key)) => Ok(Caller::ApiKey(api_key)),
+                Ok(None) => Err(unauthorized("The API key is invalid or was revoked")),
+                Err(err) => Err(DatabaseErrorResponses::from(err).into_response()),
+            };
+        }
+
+        let Some(token) = session_token(headers) else {
+            return Ok(Caller::Anonymous);
+        };
+        // An expired session leaves the caller anonymous, able to keep reading
+        match movies_core::find_session_user(db, token).await {
+            Ok(Some(user)) => Ok(Caller::User(user)),
+            Ok(None) => Ok(Caller::Anonymous),
+            Err(err) => Err(DatabaseErrorResponses::from(err).into_response()),
+        }
+    }
+}
+
+/// Extracts the caller of a request, rejecting the request unless the role of the caller allows
+/// the operation `O`.
+pub struct Authorized<O> {
+    pub caller: Caller,
+    operation: PhantomData<O>,
+}
+
+#[async_trait]
+impl<O, State> FromRequestParts<State> for Authorized<O>
+where
+    O: Operation,
+    DatabaseConnection: FromRef<State>,
+    State: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let db = DatabaseConnection::from_ref(state);
+        let caller = Caller::identify(&parts.headers, &db).await?;
+
+        if let Some(rejection) = Authorized::<O>::rejection(&caller) {
+            return Err(rejection);
+        }
+
+        Ok(Authorized {
+            caller,
+            operation: PhantomData,
+        })
+    }
+}
+
+impl<O: Operation> Authorized<O> {
+    /// Response rejecting `caller` unless their role allows the operation, as unauthenticated if
+    /// they are anonymous and as forbidden otherwise.
+    fn rejection(caller: &Caller) -> Option<Response> {
+        if caller.role().grants(O::ROLE) {
+            return None;
+        }
+
+        let detail = format!("This route needs the `{}` role", O::ROLE.to_value());
+        Some(match caller {
+            Caller::Anonymous => unauthorized(&format!("{detail}, send an API key or log in")),
+            _ => AuthErrorResponses::Forbidden(Problem::forbidden(detail)).into_response(),
+        })
+    }
+}
+
+/// Extracts the user logged in with the session cookie, rejecting the request if there is none.
+///
+/// Wrap it in an `Option` for routes open to anonymous users as well.
+pub struct CurrentUser(pub user::Model);
+
+#[async_trait]
+impl<State> FromRequestParts<State> for CurrentUser
+where
+    DatabaseConnection: FromRef<State>,
+    State: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let Some(token) = session_token(&parts.headers) else {
+            return Err(unauthorized("This route needs a logged in user"));
+        };
+
+        let db = DatabaseConnection::from_ref(state);
+        match movies_core::find_session_user(&db, token).await {
+            Ok(Some(user)) => Ok(CurrentUser(user)),
+            Ok(None) => Err(unauthorized("The session expired, log in again")),
+            Err(err) => Err(DatabaseErrorResponses::from(err).into_response()),
+        }
+    }
+}
+
+/// Token of the session cookie sent with a request, if any.
+pub(crate) fn session_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, token)| token)
+        .filter(|token| !token.is_empty())
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then(|| token.trim())
+        .filter(|token| !token.is_empty())
+}
+
+fn unauthorized(detail: &str) -> Response {
+    let mut response =
+        AuthErrorResponses::Unauthorized(Problem::unauthorized(detail)).into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// Declare the security schemes referenced by the routes needing an API key or a logged in user.
+pub fn add_security_schemes(api_docs: &mut OpenApi) {
+    let components = api_docs.components.get_or_insert_with(Default::default);
+
+    components.add_security_scheme(
+        SECURITY_SCHEME,
+        SecurityScheme::Http(
+            HttpBuilder::new()
+                .scheme(HttpAuthScheme::Bearer)
+                .description(Some(
+                    "API key minted with `movies-website api-key mint`, whose `read`, `write` \
+                     or `admin` scope makes callers viewers, editors or admins",
+                ))
+                .build(),
+        ),
+    );
+    components.add_security_scheme(
+        SESSION_SECURITY_SCHEME,
+        SecurityScheme::ApiKey(ApiKeyIn::Cookie(ApiKeyValue::with_description(
+            SESSION_COOKIE,
+            "Session cookie set by `POST /auth/login`, callers having the role of the user",
+        ))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{Request, StatusCode};
+    use movies_core::sea_orm::{ConnectionTrait, Database, DbBackend, Schema};
+    use movies_core::{create_api_key, create_session, create_user, revoke_api_key};
+    use movies_entity::sea_orm_active_enums::ApiKeyScope;
+
+    use super::*;
+
+    async fn prepare_test_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let schema = Schema::new(DbBackend::Sqlite);
+
+        let statements = [
+            schema.create_table_from_entity(movies_entity::prelude::ApiKey),
+            schema.create_table_from_entity(movies_entity::prelude::User),
+            schema.create_table_from_entity(movies_entity::prelude::Session),
+        ];
+        for statement in statements {
+            db.execute(db.get_database_backend().build(&statement))
+                .await
+                .unwrap();
+        }
+
+        db
+    }
+
+    fn user(role: UserRole) -> Caller {
+        Caller::User(user::Model {
+            id: 1,
+            email: "jane@example.com".to_owned(),
+            display_name: "Jane".to_owned(),
+            password_hash: None,
+            role,
+            oidc_issuer: None,
+            oidc_subject: None,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    fn api_key(scope: ApiKeyScope) -> Caller {
+        Caller::ApiKey(api_key::Model {
+            id: 1,
+            name: "ci".to_owned(),
+            prefix: "mvk_abcd".to_owned(),
+            key_hash: String::new(),
+            scope,
+            created_at: chrono::Utc::now(),
+            revoked_at: None,
+        })
+    }
+
+    /// Which of view, edit, delete, manage users and moderate `caller` may do.
+    fn grants(caller: &Caller) -> [bool; 5] {
+        [
+            Authorized::<View>::rejection(caller).is_none(),
+            Authorized::<Edit>::rejection(caller).is_none(),
+            Authorized::<Delete>::rejection(caller).is_none(),
+            Authorized::<ManageUsers>::rejection(caller).is_none(),
+            Authorized::<Moderate>::rejection(caller).is_none(),
+        ]
+    }
+
+    async fn extract<O: Operation>(
+        db: &DatabaseConnection,
+        header: Option<(header::HeaderName, String)>,
+    ) -> Result<Authorized<O>, Response> {
+        let mut request = Request::builder();
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+
+        Authorized::<O>::from_request_parts(&mut parts, db).await
+    }
+
+    #[test]
+    fn grant_operations_by_role() {
+        let callers = [
+            Caller::Anonymous,
+            user(UserRole::Viewer),
+            user(UserRole::Editor),
+            user(UserRole::Admin),
+            api_key(ApiKeyScope::Read),
+            api_key(ApiKeyScope::Write),
+            api_key(ApiKeyScope::Admin),
+        ];
+
+        let grants = callers.iter().map(grants).collect::<Vec<_>>();
+
+        let viewer = [true, false, false, false, false];
+        let editor = [true, true, false, false, false];
+        let admin = [true; 5];
+        assert_eq!(
+            grants,
+            [viewer, viewer, editor, admin, viewer, editor, admin]
+        );
+    }
+
+    #[test]
+    fn reject_anonymous_callers_as_unauthorized_and_others_as_forbidden() {
+        let anonymous = Authorized::<Edit>::rejection(&Caller::Anonymous).unwrap();
+        let viewer = Authorized::<Edit>::rejection(&user(UserRole::Viewer)).unwrap();
+        let read_key = Authorized::<Delete>::rejection(&api_key(ApiKeyScope::Read)).unwrap();
+
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(anonymous.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        assert_eq!(viewer.status(), StatusCode::FORBIDDEN);
+        assert!(!viewer.headers().contains_key(header::WWW_AUTHENTICATE));
+        assert_eq!(read_key.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn identify_callers_from_api_keys_and_sessions() {
+        let db = prepare_test_db().await;
+        let write_key = create_api_key(&db, "ci".to_owned(), ApiKeyScope::Write)
+            .await
+            .unwrap();
+        let revoked_key = create_api_key(&db, "old".to_owned(), ApiKeyScope::Admin)
+            .await
+            .unwrap();
+        revoke_api_key(&db, revoked_key.api_key.id).await.unwrap();
+        let jane = create_user(&db, "jane@example.com", "Jane".to_owned(), String::new())
+            .await
+            .unwrap();
+        let session = create_session(&db, jane.id, chrono::Duration::hours(1))
+            .await
+            .unwrap();
+        let bearer = |key: &str| Some((header::AUTHORIZATION, format!("Bearer {key}")));
+        let cookie = |token: &str| Some((header::COOKIE, format!("{SESSION_COOKIE}={token}")));
+
+        let anonymous = extract::<View>(&db, None).await;
+        let with_key = extract::<Edit>(&db, bearer(&write_key.key)).await;
+        let with_revoked_key = extract::<View>(&db, bearer(&revoked_key.key)).await;
+        let with_unknown_key = extract::<View>(&db, bearer("mvk_unknown")).await;
+        let with_session = extract::<View>(&db, cookie(&session.token)).await;
+        let with_expired_session = extract::<View>(&db, cookie("expired")).await;
+        let viewer_editing = extract::<Edit>(&db, cookie(&session.token)).await;
+
+        assert!(matches!(anonymous.unwrap().caller, Caller::Anonymous));
+        assert!(
+            matches!(with_key.unwrap().caller, Caller::ApiKey(key) if key.id == write_key.api_key.id)
+        );
+        for rejected in [with_revoked_key, with_unknown_key] {
+            assert_eq!(rejected.err().unwrap().status(), StatusCode::UNAUTHORIZED);
+        }
+        assert!(matches!(with_session.unwrap().caller, Caller::User(user) if user.id == jane.id));
+        // a stale session cookie falls back to anonymous, unlike a bad api key
+        assert!(matches!(
+            with_expired_session.unwrap().caller,
+            Caller::Anonymous
+        ));
+        assert_eq!(
+            viewer_editing.err().unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+}
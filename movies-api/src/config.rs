@@ -0,0 +1,634 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use movies_core::{RatingScale, MAX_RATING, MIN_RATING};
+use serde::Deserialize;
+use url::Url;
+
+/// Config file read when none is given explicitly, if it exists.
+pub const DEFAULT_CONFIG_FILE: &str = "movies.toml";
+
+/// Environment variable holding the path of the config file.
+pub const CONFIG_FILE_VAR: &str = "MOVIES_CONFIG";
+
+/// Settings of the server, resolved from every configuration source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub log: LogConfig,
+    pub otel: OtelConfig,
+    pub session: SessionConfig,
+    /// Login through an OpenID Connect provider, if configured
+    pub oidc: Option<OidcConfig>,
+    pub search: SearchConfig,
+    /// Scores accepted for the ratings of editors and users
+    pub ratings: RatingScale,
+    /// What to do with pending migrations when the server starts
+    pub migrations: MigrationMode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// How long in-flight requests may take to complete once a shutdown is requested
+    pub shutdown_timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout: Duration,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    pub format: LogFormat,
+}
+
+/// Export of traces to an OpenTelemetry collector, when built with the `otel` feature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtelConfig {
+    /// Base URL of the OTLP/HTTP endpoint of the collector, traces are only exported if it is set
+    pub endpoint: Option<String>,
+    /// Share of the traces started by this server that are exported, from 0 to 1
+    pub sampling_ratio: f64,
+}
+
+/// Sessions of the users logged in with a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// How long users stay logged in
+    pub ttl: Duration,
+    /// Whether the session cookie is only sent over HTTPS, which should only be turned off when
+    /// serving over plain HTTP elsewhere than on localhost
+    pub secure_cookie: bool,
+}
+
+/// Login through an OpenID Connect provider with the authorization code flow and PKCE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcConfig {
+    /// Issuer of the provider, under which its discovery document is served
+    pub issuer_url: String,
+    pub client_id: String,
+    /// Only needed for confidential clients
+    pub client_secret: Option<String>,
+    /// URL of `/auth/oidc/callback` as reached by browsers, registered with the provider
+    pub redirect_url: String,
+    /// Scopes requested, which always include `openid`
+    pub scopes: Vec<String>,
+    /// Claim of the ID token holding the roles or groups of the user, as a dotted path for nested
+    /// claims; the roles of users are managed locally if unset
+    pub role_claim: Option<String>,
+    /// Values of the role claim making a user an editor
+    pub editor_roles: Vec<String>,
+    /// Values of the role claim making a user an admin
+    pub admin_roles: Vec<String>,
+}
+
+/// The search index embedded in the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// Directory the index is stored in; without one the index is held in memory. Either way it is
+    /// rebuilt from the database on every start
+    pub index_dir: Option<PathBuf>,
+}
+
+/// How log lines are written to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(
+                "unknown log format `{value}`, expected `text` or `json`"
+            )),
+        }
+    }
+}
+
+/// What to do with pending migrations when the server starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationMode {
+    /// Apply them, one replica at a time
+    #[default]
+    Apply,
+    /// Refuse to start if there are any
+    Check,
+    /// Start without looking for them
+    Skip,
+}
+
+impl FromStr for MigrationMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "apply" => Ok(MigrationMode::Apply),
+            "check" => Ok(MigrationMode::Check),
+            "skip" => Ok(MigrationMode::Skip),
+            _ => Err(format!(
+                "unknown migration mode `{value}`, expected `apply`, `check` or `skip`"
+            )),
+        }
+    }
+}
+
+/// Every problem found in the configuration sources.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigError {
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration")?;
+        for error in &self.errors {
+            write!(f, "\n  - {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Settings given by a single configuration source, any of which may be missing.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub server: ServerLayer,
+    pub database: DatabaseLayer,
+    pub log: LogLayer,
+    pub otel: OtelLayer,
+    pub session: SessionLayer,
+    pub oidc: OidcLayer,
+    pub search: SearchLayer,
+    pub ratings: RatingsLayer,
+    pub migrations: Option<MigrationMode>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerLayer {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub shutdown_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseLayer {
+    pub url: Option<String>,
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+    pub connect_timeout_secs: Option<u64>,
+    pub acquire_timeout_secs: Option<u64>,
+    pub idle_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogLayer {
+    pub format: Option<LogFormat>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OtelLayer {
+    pub endpoint: Option<String>,
+    pub sampling_ratio: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionLayer {
+    pub ttl_secs: Option<u64>,
+    pub secure_cookie: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OidcLayer {
+    pub issuer_url: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub redirect_url: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    pub role_claim: Option<String>,
+    pub editor_roles: Option<Vec<String>>,
+    pub admin_roles: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchLayer {
+    pub index_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RatingsLayer {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+impl OidcLayer {
+    fn is_empty(&self) -> bool {
+        *self == OidcLayer::default()
+    }
+}
+
+impl ConfigLayer {
+    /// Parse the content of a TOML config file.
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        toml::from_str(content).map_err(|err| ConfigError {
+            errors: vec![format!("config file: {}", err.message())],
+        })
+    }
+
+    /// Read the config file at `path`, or the default one if there is one.
+    pub fn from_file(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Path::new(DEFAULT_CONFIG_FILE),
+            None => return Ok(ConfigLayer::default()),
+        };
+
+        let content = std::fs::read_to_string(path).map_err(|err| ConfigError {
+            errors: vec![format!("config file `{}`: {err}", path.display())],
+        })?;
+
+        ConfigLayer::from_toml(&content).map_err(|ConfigError { errors }| ConfigError {
+            errors: errors
+                .into_iter()
+                .map(|error| format!("{error} in `{}`", path.display()))
+                .collect(),
+        })
+    }
+
+    /// Pick the settings out of environment variables. Empty variables are ignored.
+    pub fn from_env_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut errors = vec![];
+        let layer = ConfigLayer::read_env_vars(vars, &mut errors);
+
+        if errors.is_empty() {
+            Ok(layer)
+        } else {
+            Err(ConfigError { errors })
+        }
+    }
+
+    /// Like `from_env_vars`, keeping the valid variables when others are invalid.
+    fn read_env_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+        errors: &mut Vec<String>,
+    ) -> Self {
+        let mut layer = ConfigLayer::default();
+
+        for (name, value) in vars {
+            if value.is_empty() {
+                continue;
+            }
+
+            match name.as_str() {
+                "HOST" => layer.server.host = Some(value),
+                "PORT" => layer.server.port = parse_var(&name, &value, errors),
+                "SHUTDOWN_TIMEOUT_SECS" => {
+                    layer.server.shutdown_timeout_secs = parse_var(&name, &value, errors)
+                }
+                "DATABASE_URL" => layer.database.url = Some(value),
+                "DATABASE_MAX_CONNECTIONS" => {
+                    layer.database.max_connections = parse_var(&name, &value, errors)
+                }
+                "DATABASE_MIN_CONNECTIONS" => {
+                    layer.database.min_connections = parse_var(&name, &value, errors)
+                }
+                "DATABASE_CONNECT_TIMEOUT_SECS" => {
+                    layer.database.connect_timeout_secs = parse_var(&name, &value, errors)
+                }
+                "DATABASE_ACQUIRE_TIMEOUT_SECS" => {
+                    layer.database.acquire_timeout_secs = parse_var(&name, &value, errors)
+                }
+                "DATABASE_IDLE_TIMEOUT_SECS" => {
+                    layer.database.idle_timeout_secs = parse_var(&name, &value, errors)
+                }
+                "LOG_FORMAT" => layer.log.format = parse_var(&name, &value, errors),
+                "OTEL_EXPORTER_OTLP_ENDPOINT" => layer.otel.endpoint = Some(value),
+                "OTEL_TRACES_SAMPLER_ARG" => {
+                    layer.otel.sampling_ratio = parse_var(&name, &value, errors)
+                }
+                "SESSION_TTL_SECS" => layer.session.ttl_secs = parse_var(&name, &value, errors),
+                "SESSION_SECURE_COOKIE" => {
+                    layer.session.secure_cookie = parse_var(&name, &value, errors)
+                }
+                "OIDC_ISSUER_URL" => layer.oidc.issuer_url = Some(value),
+                "OIDC_CLIENT_ID" => layer.oidc.client_id = Some(value),
+                "OIDC_CLIENT_SECRET" => layer.oidc.client_secret = Some(value),
+                "OIDC_REDIRECT_URL" => layer.oidc.redirect_url = Some(value),
+                "OIDC_SCOPES" => layer.oidc.scopes = Some(split_list(&value)),
+                "OIDC_ROLE_CLAIM" => layer.oidc.role_claim = Some(value),
+                "OIDC_EDITOR_ROLES" => layer.oidc.editor_roles = Some(split_list(&value)),
+                "OIDC_ADMIN_ROLES" => layer.oidc.admin_roles = Some(split_list(&value)),
+                "SEARCH_INDEX_DIR" => layer.search.index_dir = Some(value.into()),
+                "RATING_MIN" => layer.ratings.min = parse_var(&name, &value, errors),
+                "RATING_MAX" => layer.ratings.max = parse_var(&name, &value, errors),
+                "MIGRATIONS" => layer.migrations = parse_var(&name, &value, errors),
+                _ => {}
+            }
+        }
+
+        layer
+    }
+
+    /// Combine with a layer of higher precedence, whose settings win when both have them.
+    pub fn merge(self, other: ConfigLayer) -> ConfigLayer {
+        ConfigLayer {
+            server: ServerLayer {
+                host: other.server.host.or(self.server.host),
+                port: other.server.port.or(self.server.port),
+                shutdown_timeout_secs: other
+                    .server
+                    .shutdown_timeout_secs
+                    .or(self.server.shutdown_timeout_secs),
+            },
+            database: DatabaseLayer {
+                url: other.database.url.or(self.database.url),
+                max_connections: other
+                    .database
+                    .max_connections
+                    .or(self.database.max_connections),
+                min_connections: other
+                    .database
+                    .min_connections
+                    .or(self.database.min_connections),
+                connect_timeout_secs: other
+                    .database
+                    .connect_timeout_secs
+                    .or(self.database.connect_timeout_secs),
+                acquire_timeout_secs: other
+                    .database
+                    .acquire_timeout_secs
+                    .or(self.database.acquire_timeout_secs),
+                idle_timeout_secs: other
+                    .database
+                    .idle_timeout_secs
+                    .or(self.database.idle_timeout_secs),
+            },
+            log: LogLayer {
+                format: other.log.format.or(self.log.format),
+            },
+            otel: OtelLayer {
+                endpoint: other.otel.endpoint.or(self.otel.endpoint),
+                sampling_ratio: other.otel.sampling_ratio.or(self.otel.sampling_ratio),
+            },
+            session: SessionLayer {
+                ttl_secs: other.session.ttl_secs.or(self.session.ttl_secs),
+                secure_cookie: other.session.secure_cookie.or(self.session.secure_cookie),
+            },
+            oidc: OidcLayer {
+                issuer_url: other.oidc.issuer_url.or(self.oidc.issuer_url),
+                client_id: other.oidc.client_id.or(self.oidc.client_id),
+                client_secret: other.oidc.client_secret.or(self.oidc.client_secret),
+                redirect_url: other.oidc.redirect_url.or(self.oidc.redirect_url),
+                scopes: other.oidc.scopes.or(self.oidc.scopes),
+                role_claim: other.oidc.role_claim.or(self.oidc.role_claim),
+                editor_roles: other.oidc.editor_roles.or(self.oidc.editor_roles),
+                admin_roles: other.oidc.admin_roles.or(self.oidc.admin_roles),
+            },
+            search: SearchLayer {
+                index_dir: other.search.index_dir.or(self.search.index_dir),
+            },
+            ratings: RatingsLayer {
+                min: other.ratings.min.or(self.ratings.min),
+                max: other.ratings.max.or(self.ratings.max),
+            },
+            migrations: other.migrations.or(self.migrations),
+        }
+    }
+
+    /// Fill in the defaults and check the settings, returning all the errors found.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        let mut errors = vec![];
+
+        let host = self.server.host.unwrap_or_else(|| "127.0.0.1".to_owned());
+        if host.trim().is_empty() {
+            errors.push("server.host must not be empty".to_owned());
+        }
+
+        let url = self.database.url.unwrap_or_default();
+        if url.is_empty() {
+            errors.push("database.url must be set, for instance through DATABASE_URL".to_owned());
+        }
+
+        let max_connections = self.database.max_connections.unwrap_or(10);
+        if max_connections == 0 {
+            errors.push("database.max_connections must be at least 1".to_owned());
+        }
+
+        let min_connections = self.database.min_connections.unwrap_or(0);
+        if min_connections > max_connections {
+            errors.push(format!(
+                "database.min_connections must not be greater than database.max_connections ({max_connections})"
+            ));
+        }
+
+        let mut timeout = |name: &str, secs: Option<u64>, default: u64| {
+            let secs = secs.unwrap_or(default);
+            if secs == 0 {
+                errors.push(format!("database.{name} must be at least 1 second"));
+            }
+            Duration::from_secs(secs)
+        };
+        let connect_timeout = timeout(
+            "connect_timeout_secs",
+            self.database.connect_timeout_secs,
+            10,
+        );
+        let acquire_timeout = timeout(
+            "acquire_timeout_secs",
+            self.database.acquire_timeout_secs,
+            30,
+        );
+        let idle_timeout = timeout("idle_timeout_secs", self.database.idle_timeout_secs, 600);
+
+        let sampling_ratio = self.otel.sampling_ratio.unwrap_or(1.0);
+        if !(0.0..=1.0).contains(&sampling_ratio) {
+            errors.push("otel.sampling_ratio must be between 0 and 1".to_owned());
+        }
+
+        const DAY_SECS: u64 = 24 * 60 * 60;
+        let session_ttl_secs = self.session.ttl_secs.unwrap_or(14 * DAY_SECS);
+        if !(1..=366 * DAY_SECS).contains(&session_ttl_secs) {
+            errors.push("session.ttl_secs must be between 1 second and a year".to_owned());
+        }
+
+        let oidc = build_oidc(self.oidc, &mut errors);
+
+        // The CHECK constraints of the database bound the scale
+        let ratings = RatingScale::new(
+            self.ratings.min.unwrap_or(MIN_RATING),
+            self.ratings.max.unwrap_or(MAX_RATING),
+        )
+        .unwrap_or_else(|err| {
+            errors.push(format!("ratings.min and ratings.max {err}"));
+            RatingScale::default()
+        });
+
+        if !errors.is_empty() {
+            return Err(ConfigError { errors });
+        }
+
+        Ok(Config {
+            server: ServerConfig {
+                host,
+                port: self.server.port.unwrap_or(8080),
+                shutdown_timeout: Duration::from_secs(
+                    self.server.shutdown_timeout_secs.unwrap_or(30),
+                ),
+            },
+            database: DatabaseConfig {
+                url,
+                max_connections,
+                min_connections,
+                connect_timeout,
+                acquire_timeout,
+                idle_timeout,
+            },
+            log: LogConfig {
+                format: self.log.format.unwrap_or_default(),
+            },
+            otel: OtelConfig {
+                endpoint: self.otel.endpoint,
+                sampling_ratio,
+            },
+            session: SessionConfig {
+                ttl: Duration::from_secs(session_ttl_secs),
+                secure_cookie: self.session.secure_cookie.unwrap_or(true),
+            },
+            oidc,
+            search: SearchConfig {
+                index_dir: self.search.index_dir,
+            },
+            ratings,
+            migrations: self.migrations.unwrap_or_default(),
+        })
+    }
+}
+
+/// OIDC login is only enabled when an issuer is set, in which case the client must be set too.
+fn build_oidc(layer: OidcLayer, errors: &mut Vec<String>) -> Option<OidcConfig> {
+    let Some(issuer_url) = layer.issuer_url.clone() else {
+        if !layer.is_empty() {
+            errors.push("oidc.issuer_url must be set to log in with OpenID Connect".to_owned());
+        }
+        return None;
+    };
+
+    let mut check_url = |name: &str, value: &str| match Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        _ => errors.push(format!("oidc.{name} must be an http or https URL")),
+    };
+    check_url("issuer_url", &issuer_url);
+
+    let redirect_url = layer.redirect_url.unwrap_or_default();
+    check_url("redirect_url", &redirect_url);
+
+    let client_id = layer.client_id.unwrap_or_default();
+    if client_id.is_empty() {
+        errors.push("oidc.client_id must be set".to_owned());
+    }
+
+    let mut scopes = layer
+        .scopes
+        .unwrap_or_else(|| vec!["email".to_owned(), "profile".to_owned()]);
+    if !scopes.iter().any(|scope| scope == "openid") {
+        scopes.insert(0, "openid".to_owned());
+    }
+
+    Some(OidcConfig {
+        issuer_url,
+        client_id,
+        client_secret: layer.client_secret,
+        redirect_url,
+        scopes,
+        role_claim: layer.role_claim,
+        editor_roles: layer.editor_roles.unwrap_or_default(),
+        admin_roles: layer.admin_roles.unwrap_or_default(),
+    })
+}
+
+/// Split a comma separated list, ignoring blank items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn parse_var<T>(name: &str, value: &str, errors: &mut Vec<String>) -> Option<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(err) => {
+            errors.push(format!("{name}: invalid value `{value}`: {err}"));
+            None
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file, then the environment, then `overrides`, each taking precedence over
+    /// the previous ones.
+    ///
+    /// The config file is `config_file` if given, else the one named by `MOVIES_CONFIG`, else
+    /// `movies.toml` if it exists.
+    pub fn load(config_file: Option<&Path>, overrides: ConfigLayer) -> Result<Config, ConfigError> {
+        let config_file = config_file
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_FILE_VAR).map(Into::into));
+
+        let mut errors = vec![];
+
+        let file = ConfigLayer::from_file(config_file.as_deref()).unwrap_or_else(|err| {
+            errors.extend(err.errors);
+            ConfigLayer::default()
+        });
+        let env = ConfigLayer::read_env_vars(std::env::vars(), &mut errors);
+
+        match file.merge(env).merge(overrides).build() {
+            Ok(config) if errors.is_empty() => Ok(config),
+            Ok(_) => Err(ConfigError { errors }),
+            Err(err) => {
+                errors.extend(err.errors);
+                Err(ConfigError { errors })
+            }
+        }
+    }
+}
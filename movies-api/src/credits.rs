@@ -0,0 +1,113 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_entity::credit::Model as Credit;
+use movies_entity::sea_orm_active_enums::CreditType;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::{FromRef, State};
+use axum::response::IntoResponse;
+use axum::routing::{delete, post};
+use axum::Router;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, Delete, Edit};
+use crate::extract::{Json, Path};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(create_credit, delete_credit),
+    components(schemas(Credit, CreditType, Problem, ProblemCode)),
+    tags((name = "credits", description = "Rust Movies API"))
+)]
+pub struct CreditsApiDocs;
+
+pub fn credits_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/", post(create_credit))
+        .route("/:id", delete(delete_credit))
+        .with_state(CreditsState { db })
+}
+
+#[derive(Clone)]
+struct CreditsState {
+    db: DatabaseConnection,
+}
+
+impl FromRef<CreditsState> for DatabaseConnection {
+    fn from_ref(state: &CreditsState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum CreateCreditResponses {
+    #[response(status = OK)]
+    Success(#[json] Credit),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Credit a person on a movie
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        post,
+        path = "/credits",
+        request_body = Credit,
+        responses(CreateCreditResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "credits"
+    )]
+async fn create_credit(
+    state: State<CreditsState>,
+    _caller: Authorized<Edit>,
+    Json(data): Json<Credit>,
+) -> Result<CreateCreditResponses, DatabaseErrorResponses> {
+    match movies_core::create_credit(&state.db, data).await {
+        Ok(created_credit) => Ok(CreateCreditResponses::Success(created_credit)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(CreateCreditResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeleteCreditResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing credit by id
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+        delete,
+        path = "/credits/{id}",
+        params(
+            ("id", description = "Credit id")
+        ),
+        responses(DeleteCreditResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "credits"
+    )]
+async fn delete_credit(
+    state: State<CreditsState>,
+    _caller: Authorized<Delete>,
+    Path(id): Path<i32>,
+) -> Result<DeleteCreditResponses, DatabaseErrorResponses> {
+    match movies_core::delete_credit(&state.db, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(DeleteCreditResponses::Success)
+        }
+        Ok(_) => Ok(DeleteCreditResponses::NotFound(Problem::not_found(
+            format!("Credit with id `{id}` not found"),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,72 @@
+use axum::async_trait;
+use axum::extract::rejection::{JsonRejection, PathRejection};
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use tracing::error;
+
+use crate::responses::{Problem, ProblemCode};
+
+/// JSON request body, like [`axum::Json`], but rejecting bodies that cannot be read with problem
+/// details: 400 for malformed JSON, 415 without a JSON content type and 422 for JSON not matching
+/// the expected schema.
+pub struct Json<T>(pub T);
+
+#[async_trait]
+impl<T, State> FromRequest<State> for Json<T>
+where
+    axum::Json<T>: FromRequest<State, Rejection = JsonRejection>,
+    State: Send + Sync,
+{
+    type Rejection = Problem;
+
+    async fn from_request(request: Request, state: &State) -> Result<Self, Self::Rejection> {
+        match axum::Json::<T>::from_request(request, state).await {
+            Ok(axum::Json(value)) => Ok(Json(value)),
+            Err(rejection) => {
+                let status = rejection.status();
+                let code = match status {
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE => ProblemCode::UnsupportedMediaType,
+                    StatusCode::UNPROCESSABLE_ENTITY => ProblemCode::ValidationFailed,
+                    _ => ProblemCode::BadRequest,
+                };
+
+                Err(Problem::new(status, code, rejection.body_text()))
+            }
+        }
+    }
+}
+
+/// Path parameters, like [`axum::extract::Path`], but rejecting parameters that cannot be parsed
+/// with problem details: 400, or 500 when the route does not match the parameters expected.
+pub struct Path<T>(pub T);
+
+#[async_trait]
+impl<T, State> FromRequestParts<State> for Path<T>
+where
+    axum::extract::Path<T>: FromRequestParts<State, Rejection = PathRejection>,
+    State: Send + Sync,
+{
+    type Rejection = Problem;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        match axum::extract::Path::<T>::from_request_parts(parts, state).await {
+            Ok(axum::extract::Path(value)) => Ok(Path(value)),
+            Err(rejection) if rejection.status().is_client_error() => {
+                Err(Problem::bad_request(rejection.body_text()))
+            }
+            Err(rejection) => {
+                error!(
+                    "Invalid path parameters of route: {}",
+                    rejection.body_text()
+                );
+
+                Err(Problem::new(
+                    rejection.status(),
+                    ProblemCode::InternalError,
+                    "Internal error",
+                ))
+            }
+        }
+    }
+}
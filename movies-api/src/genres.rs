@@ -0,0 +1,258 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_core::{CreateGenre, FieldError, Pagination, Validate};
+use movies_entity::genre::Model as Genre;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, Delete, Edit, View};
+use crate::extract::{Json, Path};
+use crate::pagination::{GenresPage, Page, PaginationParams};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_genres, create_genre, get_genre, delete_genre, update_genre),
+    components(schemas(Genre, CreateGenre, GenresPage, Problem, ProblemCode, FieldError)),
+    tags((
+        name = "genres",
+        description = "Genres of movies, assigned to them through `/movies/{id}/genres`"
+    ))
+)]
+pub struct GenresApiDocs;
+
+pub fn genres_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/", get(list_genres).post(create_genre))
+        .route(
+            "/:id",
+            get(get_genre).delete(delete_genre).put(update_genre),
+        )
+        .with_state(GenresState { db })
+}
+
+#[derive(Clone)]
+struct GenresState {
+    db: DatabaseConnection,
+}
+
+impl FromRef<GenresState> for DatabaseConnection {
+    fn from_ref(state: &GenresState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListGenresResponses {
+    #[response(status = OK)]
+    Success(#[json] GenresPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Get a page of genres
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/genres",
+    params(PaginationParams),
+    responses(ListGenresResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "genres"
+)]
+async fn list_genres(
+    state: State<GenresState>,
+    _caller: Authorized<View>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+) -> Result<ListGenresResponses, DatabaseErrorResponses> {
+    let pagination: Pagination = match pagination {
+        Ok(Query(pagination)) => match (&pagination).try_into() {
+            Ok(pagination) => pagination,
+            Err(message) => {
+                return Ok(ListGenresResponses::BadRequest(Problem::bad_request(
+                    message,
+                )))
+            }
+        },
+        Err(rejection) => {
+            return Ok(ListGenresResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    match movies_core::get_all_genres(&state.db, pagination).await {
+        Ok(page) => Ok(ListGenresResponses::Success(Page::new(
+            page,
+            "/genres",
+            &(),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum CreateGenreResponses {
+    #[response(status = OK)]
+    Success(#[json] Genre),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Create a genre
+///
+/// Needs the `editor` role. Genre names are unique.
+#[utoipa::path(
+        post,
+        path = "/genres",
+        request_body = CreateGenre,
+        responses(CreateGenreResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "genres"
+    )]
+async fn create_genre(
+    state: State<GenresState>,
+    _caller: Authorized<Edit>,
+    Json(data): Json<CreateGenre>,
+) -> Result<CreateGenreResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(CreateGenreResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::create_genre(&state.db, data).await {
+        Ok(genre) => Ok(CreateGenreResponses::Success(genre)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetGenreResponses {
+    #[response(status = OK)]
+    Success(#[json] Genre),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Get an existing genre by id
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+        get,
+        path = "/genres/{id}",
+        params(
+            ("id", description = "Genre id")
+        ),
+        responses(GetGenreResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "genres"
+    )]
+async fn get_genre(
+    state: State<GenresState>,
+    _caller: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetGenreResponses, DatabaseErrorResponses> {
+    match movies_core::get_genre(&state.db, id).await {
+        Ok(Some(genre)) => Ok(GetGenreResponses::Success(genre)),
+        Ok(None) => Ok(GetGenreResponses::NotFound(Problem::not_found(format!(
+            "Genre with id `{id}` not found"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeleteGenreResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing genre by id, taking it away from its movies
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+        delete,
+        path = "/genres/{id}",
+        params(
+            ("id", description = "Genre id")
+        ),
+        responses(DeleteGenreResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "genres"
+    )]
+async fn delete_genre(
+    state: State<GenresState>,
+    _caller: Authorized<Delete>,
+    Path(id): Path<i32>,
+) -> Result<DeleteGenreResponses, DatabaseErrorResponses> {
+    match movies_core::delete_genre(&state.db, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(DeleteGenreResponses::Success)
+        }
+        Ok(_) => Ok(DeleteGenreResponses::NotFound(Problem::not_found(format!(
+            "Genre with id `{id}` not found"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdateGenreResponses {
+    #[response(status = OK)]
+    Success(#[json] Genre),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Rename an existing genre by id
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        put,
+        path = "/genres/{id}",
+        params(
+            ("id", description = "Genre id")
+        ),
+        request_body = CreateGenre,
+        responses(UpdateGenreResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "genres"
+    )]
+async fn update_genre(
+    state: State<GenresState>,
+    _caller: Authorized<Edit>,
+    Path(id): Path<i32>,
+    Json(data): Json<CreateGenre>,
+) -> Result<UpdateGenreResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(UpdateGenreResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::update_genre(&state.db, id, data).await {
+        Ok(genre) => Ok(UpdateGenreResponses::Success(genre)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(UpdateGenreResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
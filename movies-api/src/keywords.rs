@@ -0,0 +1,259 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_core::{CreateKeyword, FieldError, Pagination, Validate};
+use movies_entity::keyword::Model as Keyword;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, Delete, Edit, View};
+use crate::extract::{Json, Path};
+use crate::pagination::{KeywordsPage, Page, PaginationParams};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_keywords, create_keyword, get_keyword, delete_keyword, update_keyword),
+    components(schemas(Keyword, CreateKeyword, KeywordsPage, Problem, ProblemCode, FieldError)),
+    tags((
+        name = "keywords",
+        description = "Lowercase keywords tagging movies, assigned to them through \
+                       `/movies/{id}/keywords`"
+    ))
+)]
+pub struct KeywordsApiDocs;
+
+pub fn keywords_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/", get(list_keywords).post(create_keyword))
+        .route(
+            "/:id",
+            get(get_keyword).delete(delete_keyword).put(update_keyword),
+        )
+        .with_state(KeywordsState { db })
+}
+
+#[derive(Clone)]
+struct KeywordsState {
+    db: DatabaseConnection,
+}
+
+impl FromRef<KeywordsState> for DatabaseConnection {
+    fn from_ref(state: &KeywordsState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListKeywordsResponses {
+    #[response(status = OK)]
+    Success(#[json] KeywordsPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Get a page of keywords
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/keywords",
+    params(PaginationParams),
+    responses(ListKeywordsResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "keywords"
+)]
+async fn list_keywords(
+    state: State<KeywordsState>,
+    _caller: Authorized<View>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+) -> Result<ListKeywordsResponses, DatabaseErrorResponses> {
+    let pagination: Pagination = match pagination {
+        Ok(Query(pagination)) => match (&pagination).try_into() {
+            Ok(pagination) => pagination,
+            Err(message) => {
+                return Ok(ListKeywordsResponses::BadRequest(Problem::bad_request(
+                    message,
+                )))
+            }
+        },
+        Err(rejection) => {
+            return Ok(ListKeywordsResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    match movies_core::get_all_keywords(&state.db, pagination).await {
+        Ok(page) => Ok(ListKeywordsResponses::Success(Page::new(
+            page,
+            "/keywords",
+            &(),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum CreateKeywordResponses {
+    #[response(status = OK)]
+    Success(#[json] Keyword),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Create a keyword
+///
+/// Needs the `editor` role. Keywords are lowercased, and unique.
+#[utoipa::path(
+        post,
+        path = "/keywords",
+        request_body = CreateKeyword,
+        responses(CreateKeywordResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "keywords"
+    )]
+async fn create_keyword(
+    state: State<KeywordsState>,
+    _caller: Authorized<Edit>,
+    Json(data): Json<CreateKeyword>,
+) -> Result<CreateKeywordResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(CreateKeywordResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::create_keyword(&state.db, data).await {
+        Ok(keyword) => Ok(CreateKeywordResponses::Success(keyword)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetKeywordResponses {
+    #[response(status = OK)]
+    Success(#[json] Keyword),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Get an existing keyword by id
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+        get,
+        path = "/keywords/{id}",
+        params(
+            ("id", description = "Keyword id")
+        ),
+        responses(GetKeywordResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "keywords"
+    )]
+async fn get_keyword(
+    state: State<KeywordsState>,
+    _caller: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetKeywordResponses, DatabaseErrorResponses> {
+    match movies_core::get_keyword(&state.db, id).await {
+        Ok(Some(keyword)) => Ok(GetKeywordResponses::Success(keyword)),
+        Ok(None) => Ok(GetKeywordResponses::NotFound(Problem::not_found(format!(
+            "Keyword with id `{id}` not found"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeleteKeywordResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing keyword by id, taking it away from its movies
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+        delete,
+        path = "/keywords/{id}",
+        params(
+            ("id", description = "Keyword id")
+        ),
+        responses(DeleteKeywordResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "keywords"
+    )]
+async fn delete_keyword(
+    state: State<KeywordsState>,
+    _caller: Authorized<Delete>,
+    Path(id): Path<i32>,
+) -> Result<DeleteKeywordResponses, DatabaseErrorResponses> {
+    match movies_core::delete_keyword(&state.db, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(DeleteKeywordResponses::Success)
+        }
+        Ok(_) => Ok(DeleteKeywordResponses::NotFound(Problem::not_found(
+            format!("Keyword with id `{id}` not found"),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdateKeywordResponses {
+    #[response(status = OK)]
+    Success(#[json] Keyword),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Rename an existing keyword by id
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        put,
+        path = "/keywords/{id}",
+        params(
+            ("id", description = "Keyword id")
+        ),
+        request_body = CreateKeyword,
+        responses(UpdateKeywordResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "keywords"
+    )]
+async fn update_keyword(
+    state: State<KeywordsState>,
+    _caller: Authorized<Edit>,
+    Path(id): Path<i32>,
+    Json(data): Json<CreateKeyword>,
+) -> Result<UpdateKeywordResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(UpdateKeywordResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::update_keyword(&state.db, id, data).await {
+        Ok(keyword) => Ok(UpdateKeywordResponses::Success(keyword)),
+        Err(DbErr::RecordNotFound(message)) => Ok(UpdateKeywordResponses::NotFound(
+            Problem::not_found(message),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
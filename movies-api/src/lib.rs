@@ -1,60 +1,395 @@
-use axum::Router;
+use accounts::{accounts_routes, AccountsApiDocs};
+use anyhow::Context;
+use api_keys::{run_api_key_command, ApiKeyCommand};
+use axum::{middleware, Router};
+use config::{Config, ConfigLayer, DatabaseConfig, LogFormat, SearchConfig, ServerConfig};
+use credits::{credits_routes, CreditsApiDocs};
+use genres::{genres_routes, GenresApiDocs};
+use keywords::{keywords_routes, KeywordsApiDocs};
+use lists::{lists_routes, ListsApiDocs};
+use migrations::run_migrations;
+use monitoring::{install_recorder, metrics_routes, track_requests, MetricsApiDocs};
 use movies::{movies_routes, MoviesApiDocs};
-use movies_core::sea_orm::Database;
-use movies_migration::{Migrator, MigratorTrait};
+use movies_core::sea_orm::{DatabaseConnection, SqlxPostgresConnector};
+use movies_core::ChangeListener;
+use movies_search::{SearchIndex, Suggestions};
+use oidc::OidcClient;
+use ops::{ops_routes, OpsApiDocs};
+use persons::{persons_routes, PersonsApiDocs};
+use ratings::{ratings_routes, RatingsApiDocs};
+use request_context::request_context;
+use reviews::{reviews_routes, ReviewsApiDocs};
+use search::{search_routes, SearchApiDocs};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use std::future::IntoFuture;
+use std::path::PathBuf;
+use std::process::ExitCode;
 use std::str::FromStr;
-use std::{env, net::SocketAddr};
+use std::sync::Arc;
+use suggest::{suggest_routes, SuggestApiDocs};
 use tokio::net::TcpListener;
-use tracing::info;
+use tokio::signal;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+use users::{users_routes, UsersApiDocs};
 use utoipa::{openapi, OpenApi};
 use utoipa_swagger_ui::SwaggerUi;
 
+mod accounts;
+pub mod api_keys;
+pub mod auth;
+pub mod config;
+mod credits;
+mod extract;
+mod genres;
+mod keywords;
+mod lists;
+mod migrations;
+mod monitoring;
 mod movies;
+pub mod oidc;
+mod ops;
+#[cfg(feature = "otel")]
+mod otel;
+mod pagination;
+mod persons;
+mod ratings;
+mod request_context;
 mod responses;
+mod reviews;
+mod search;
+mod suggest;
+mod users;
 
 pub fn get_api_docs() -> openapi::OpenApi {
     #[derive(OpenApi)]
-    #[openapi(info(title = "Rust Movies", contact()))]
+    #[openapi(info(
+        title = "Rust Movies",
+        description = "Errors are served as problem details (RFC 7807) with the \
+                       `application/problem+json` content type, including requests whose body \
+                       or path cannot be read: 400 for malformed JSON or path parameters, 415 \
+                       for bodies not sent as `application/json` and 422 for JSON not matching \
+                       the schema of the body.",
+        contact()
+    ))]
     struct BaseApiDocs;
 
     let mut api_docs = BaseApiDocs::openapi();
     api_docs.merge(MoviesApiDocs::openapi());
+    api_docs.merge(PersonsApiDocs::openapi());
+    api_docs.merge(CreditsApiDocs::openapi());
+    api_docs.merge(RatingsApiDocs::openapi());
+    api_docs.merge(GenresApiDocs::openapi());
+    api_docs.merge(KeywordsApiDocs::openapi());
+    api_docs.merge(ListsApiDocs::openapi());
+    api_docs.merge(ReviewsApiDocs::openapi());
+    api_docs.merge(SearchApiDocs::openapi());
+    api_docs.merge(SuggestApiDocs::openapi());
+    api_docs.merge(AccountsApiDocs::openapi());
+    api_docs.merge(UsersApiDocs::openapi());
+    api_docs.merge(OpsApiDocs::openapi());
+    api_docs.merge(MetricsApiDocs::openapi());
+    auth::add_security_schemes(&mut api_docs);
 
     api_docs
 }
 
-#[tokio::main]
-async fn start() -> anyhow::Result<()> {
-    dotenvy::dotenv()?;
+/// What to run once configured.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Serve the API
+    Serve,
+    /// Manage the API keys
+    ApiKey(ApiKeyCommand),
+    /// Index every movie and person of the database again
+    RebuildSearchIndex,
+}
 
-    tracing_subscriber::fmt::init();
+#[tokio::main]
+async fn start(config: Config) -> anyhow::Result<()> {
+    let metrics = install_recorder().context("Cannot install the metrics recorder")?;
 
-    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set in .env file");
-    let host = env::var("HOST").expect("HOST is not set in .env file");
-    let port = env::var("PORT").expect("PORT is not set in .env file");
-    let server_url = format!("{host}:{port}");
+    let pool = connect(&config.database)
+        .await
+        .context("Database connection failed")?;
+    let conn = SqlxPostgresConnector::from_sqlx_postgres_pool(pool.clone());
+    run_migrations(&conn, config.migrations).await?;
 
-    let conn = Database::connect(db_url)
+    let index = open_search_index(&config.search, &conn).await?;
+    let suggestions = Arc::new(Suggestions::default());
+    suggestions
+        .rebuild(&conn)
         .await
-        .expect("Database connection failed");
-    Migrator::up(&conn, None).await.unwrap();
+        .context("Cannot load the suggestions")?;
+    let changes: Arc<dyn ChangeListener> = Arc::new(vec![
+        index
+            .clone()
+            .spawn_updater()
+            .context("Cannot start updating the search index")?,
+        suggestions.clone(),
+    ]);
 
     let app = Router::new()
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", get_api_docs()))
-        .nest("/movies", movies_routes(conn));
+        .nest(
+            "/movies",
+            movies_routes(conn.clone(), changes.clone(), config.ratings),
+        )
+        .nest("/persons", persons_routes(conn.clone(), changes))
+        .nest("/credits", credits_routes(conn.clone()))
+        .nest("/ratings", ratings_routes(conn.clone(), config.ratings))
+        .nest("/genres", genres_routes(conn.clone()))
+        .nest("/keywords", keywords_routes(conn.clone()))
+        .nest("/lists", lists_routes(conn.clone()))
+        .nest("/reviews", reviews_routes(conn.clone()))
+        .nest("/search", search_routes(conn.clone(), index))
+        .nest("/suggest", suggest_routes(suggestions))
+        .nest("/users", users_routes(conn.clone()))
+        .nest(
+            "/auth",
+            accounts_routes(
+                conn.clone(),
+                config.session.clone(),
+                config
+                    .oidc
+                    .clone()
+                    .map(|oidc| Arc::new(OidcClient::new(oidc))),
+            ),
+        )
+        .merge(ops_routes(conn.clone()))
+        .merge(metrics_routes(metrics, pool))
+        .route_layer(middleware::from_fn(track_requests))
+        .layer(middleware::from_fn(responses::problem_context))
+        .layer(middleware::from_fn(request_context));
 
-    let addr = SocketAddr::from_str(&server_url).unwrap();
-    let listener = TcpListener::bind(&addr).await?;
+    let ServerConfig {
+        host,
+        port,
+        shutdown_timeout,
+    } = &config.server;
+    let listener = TcpListener::bind((host.as_str(), *port))
+        .await
+        .with_context(|| format!("Cannot listen at {host}:{port}"))?;
+
+    info!("Server listening at {}", listener.local_addr()?);
 
-    info!("Server listening at {server_url}");
+    let shutdown = Arc::new(Notify::new());
+    let server = axum::serve(listener, app).with_graceful_shutdown({
+        let shutdown = shutdown.clone();
+        async move { shutdown.notified().await }
+    });
+
+    tokio::select! {
+        result = server.into_future() => result?,
+        _ = async {
+            shutdown_signal().await;
+            info!("Shutting down, waiting up to {shutdown_timeout:?} for in-flight requests");
+            shutdown.notify_one();
+            tokio::time::sleep(*shutdown_timeout).await;
+        } => warn!("Shutdown timeout elapsed, dropping in-flight requests"),
+    }
 
-    axum::serve(listener, app).await?;
+    conn.close().await?;
+    info!("Server stopped");
 
     Ok(())
 }
 
-pub fn main() {
-    if let Err(err) = start() {
-        println!("Error: {err}");
+#[tokio::main]
+async fn manage_api_keys(config: Config, command: ApiKeyCommand) -> anyhow::Result<()> {
+    let pool = connect(&config.database)
+        .await
+        .context("Database connection failed")?;
+    let conn = SqlxPostgresConnector::from_sqlx_postgres_pool(pool);
+    run_migrations(&conn, config.migrations).await?;
+
+    let result = run_api_key_command(&conn, command).await;
+    conn.close().await?;
+
+    result
+}
+
+/// Open the search index and rebuild it from the database, which happens on every start.
+async fn open_search_index(
+    config: &SearchConfig,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<Arc<SearchIndex>> {
+    let index = match &config.index_dir {
+        Some(dir) => SearchIndex::open(dir)
+            .with_context(|| format!("Cannot open the search index in `{}`", dir.display()))?,
+        None => SearchIndex::in_memory().context("Cannot create the search index")?,
+    };
+
+    // A stored index misses the changes made while the server was stopped
+    index
+        .rebuild(conn)
+        .await
+        .context("Cannot build the search index")?;
+
+    Ok(Arc::new(index))
+}
+
+#[tokio::main]
+async fn rebuild_search_index(config: Config) -> anyhow::Result<()> {
+    let Some(dir) = &config.search.index_dir else {
+        anyhow::bail!("search.index_dir must be set, the index is only held in memory otherwise");
+    };
+
+    let pool = connect(&config.database)
+        .await
+        .context("Database connection failed")?;
+    let conn = SqlxPostgresConnector::from_sqlx_postgres_pool(pool);
+    run_migrations(&conn, config.migrations).await?;
+
+    let result = match SearchIndex::open(dir) {
+        Ok(index) => index.rebuild(&conn).await.map_err(Into::into),
+        Err(err) => Err(err).with_context(|| {
+            format!(
+                "Cannot open the search index in `{}`, it may be in use by a running server",
+                dir.display()
+            )
+        }),
+    };
+    conn.close().await?;
+
+    let count = result?;
+    println!("Indexed {count} movies and persons");
+
+    Ok(())
+}
+
+/// Resolve once the process is asked to stop, by SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(err) = signal::ctrl_c().await {
+            error!("Cannot listen for SIGINT: {err}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                error!("Cannot listen for SIGTERM: {err}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {},
+        _ = terminate => {},
+    }
+}
+
+/// Flushes the traces left to export when dropped.
+struct TracingGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(err) = provider.shutdown() {
+                eprintln!("Error: cannot flush traces: {err}");
+            }
+        }
+    }
+}
+
+fn init_tracing(config: &Config) -> anyhow::Result<TracingGuard> {
+    let fmt_layer = match config.log.format {
+        LogFormat::Text => fmt::layer().boxed(),
+        LogFormat::Json => fmt::layer().json().with_span_list(false).boxed(),
+    };
+    let registry =
+        tracing_subscriber::registry().with(fmt_layer.with_filter(EnvFilter::from_default_env()));
+
+    #[cfg(feature = "otel")]
+    {
+        let provider = otel::tracer_provider(&config.otel)?;
+        registry.with(provider.as_ref().map(otel::layer)).init();
+
+        Ok(TracingGuard { provider })
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.init();
+        if config.otel.endpoint.is_some() {
+            warn!("Not exporting traces, the server is built without the `otel` feature");
+        }
+
+        Ok(TracingGuard {})
+    }
+}
+
+/// Open the database pool, which is kept around besides the `DatabaseConnection` wrapping it to
+/// report on its connections.
+async fn connect(config: &DatabaseConfig) -> anyhow::Result<PgPool> {
+    let connect_options = PgConnectOptions::from_str(&config.url)?;
+
+    let pool = PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.acquire_timeout)
+        .idle_timeout(config.idle_timeout)
+        .connect_with(connect_options);
+
+    tokio::time::timeout(config.connect_timeout, pool)
+        .await
+        .with_context(|| format!("Timed out after {:?}", config.connect_timeout))?
+        .map_err(Into::into)
+}
+
+/// Run `command`, configured by the config file, then the environment, then `overrides`.
+pub fn main(config_file: Option<PathBuf>, overrides: ConfigLayer, command: Command) -> ExitCode {
+    if let Err(err) = dotenvy::dotenv() {
+        if !err.not_found() {
+            eprintln!("Error: cannot load .env file: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let config = match Config::load(config_file.as_deref(), overrides) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let _tracing = match init_tracing(&config) {
+        Ok(guard) => guard,
+        Err(err) => {
+            eprintln!("Error: cannot set up tracing: {err:#}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match command {
+        Command::Serve => start(config),
+        Command::ApiKey(command) => manage_api_keys(config, command),
+        Command::RebuildSearchIndex => rebuild_search_index(config),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err:#}");
+            ExitCode::FAILURE
+        }
     }
 }
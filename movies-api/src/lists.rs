@@ -0,0 +1,536 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_core::{
+    AddListItem, CreateList, FieldError, ListItemResponse, ListResponse, MovieSummary, Pagination,
+    PartialList, ReorderList, UpdateListItem, Validate,
+};
+use movies_entity::list::Model as List;
+use movies_entity::list_item::Model as ListItem;
+use movies_entity::sea_orm_active_enums::ListVisibility;
+use movies_entity::user;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::{get, patch, post, put};
+use axum::Router;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, Caller, CurrentUser, View};
+use crate::extract::{Json, Path};
+use crate::pagination::{ListsPage, Page, PaginationParams};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_lists,
+        create_list,
+        get_list,
+        update_list,
+        delete_list,
+        add_list_item,
+        update_list_item,
+        remove_list_item,
+        reorder_list,
+    ),
+    components(schemas(
+        List,
+        ListItem,
+        ListVisibility,
+        ListsPage,
+        ListResponse,
+        ListItemResponse,
+        MovieSummary,
+        CreateList,
+        PartialList,
+        AddListItem,
+        UpdateListItem,
+        ReorderList,
+        Problem,
+        ProblemCode,
+        FieldError
+    )),
+    tags((
+        name = "lists",
+        description = "Lists of movies curated by users, private to them unless made public"
+    ))
+)]
+pub struct ListsApiDocs;
+
+pub fn lists_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/", get(list_lists).post(create_list))
+        .route("/:id", get(get_list).patch(update_list).delete(delete_list))
+        .route("/:id/items", post(add_list_item))
+        .route(
+            "/:id/items/:item_id",
+            patch(update_list_item).delete(remove_list_item),
+        )
+        .route("/:id/order", put(reorder_list))
+        .with_state(ListsState { db })
+}
+
+#[derive(Clone)]
+struct ListsState {
+    db: DatabaseConnection,
+}
+
+impl FromRef<ListsState> for DatabaseConnection {
+    fn from_ref(state: &ListsState) -> Self {
+        state.db.clone()
+    }
+}
+
+/// Why a user may not change a list.
+enum Denied {
+    NotFound(Problem),
+    Forbidden(Problem),
+}
+
+/// The list with this id if `user` owns it. Other users are told they may not change public
+/// lists, while private ones stay hidden from them.
+async fn owned_list(
+    db: &DatabaseConnection,
+    id: i32,
+    user: &user::Model,
+) -> Result<Result<List, Denied>, DbErr> {
+    Ok(match movies_core::get_list(db, id).await? {
+        Some(list) if list.owner_id == user.id => Ok(list),
+        Some(list) if list.visibility == ListVisibility::Public => Err(Denied::Forbidden(
+            Problem::forbidden("Only the owner of a list may change it"),
+        )),
+        _ => Err(Denied::NotFound(list_not_found(id))),
+    })
+}
+
+fn list_not_found(id: i32) -> Problem {
+    Problem::not_found(format!("List with id `{id}` not found"))
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListListsResponses {
+    #[response(status = OK)]
+    Success(#[json] ListsPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Get a page of the public lists, along with the lists of the logged in user
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/lists",
+    params(PaginationParams),
+    responses(ListListsResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "lists"
+)]
+async fn list_lists(
+    state: State<ListsState>,
+    Authorized { caller, .. }: Authorized<View>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+) -> Result<ListListsResponses, DatabaseErrorResponses> {
+    let pagination: Pagination = match pagination {
+        Ok(Query(pagination)) => match (&pagination).try_into() {
+            Ok(pagination) => pagination,
+            Err(message) => {
+                return Ok(ListListsResponses::BadRequest(Problem::bad_request(
+                    message,
+                )))
+            }
+        },
+        Err(rejection) => {
+            return Ok(ListListsResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    let viewer_id = match &caller {
+        Caller::User(user) => Some(user.id),
+        _ => None,
+    };
+
+    match movies_core::get_visible_lists(&state.db, viewer_id, pagination).await {
+        Ok(page) => Ok(ListListsResponses::Success(Page::new(page, "/lists", &()))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum CreateListResponses {
+    #[response(status = OK)]
+    Success(#[json] List),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Create a list owned by the logged in user
+#[utoipa::path(
+        post,
+        path = "/lists",
+        request_body = CreateList,
+        responses(CreateListResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn create_list(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Json(data): Json<CreateList>,
+) -> Result<CreateListResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(CreateListResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::create_list(&state.db, user.id, data).await {
+        Ok(list) => Ok(CreateListResponses::Success(list)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetListResponses {
+    #[response(status = OK)]
+    Success(#[json] ListResponse),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Get an existing list by id, with a summary of each of its movies in order
+///
+/// Needs the `viewer` role, which anonymous callers have. Private lists are only found by their
+/// owner.
+#[utoipa::path(
+        get,
+        path = "/lists/{id}",
+        params(
+            ("id", description = "List id")
+        ),
+        responses(GetListResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "lists"
+    )]
+async fn get_list(
+    state: State<ListsState>,
+    Authorized { caller, .. }: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetListResponses, DatabaseErrorResponses> {
+    let list = match movies_core::get_list(&state.db, id).await? {
+        Some(list) if list.visibility == ListVisibility::Public => list,
+        Some(list) if matches!(&caller, Caller::User(user) if user.id == list.owner_id) => list,
+        _ => return Ok(GetListResponses::NotFound(list_not_found(id))),
+    };
+
+    match movies_core::get_list_items(&state.db, id).await {
+        Ok(items) => Ok(GetListResponses::Success(ListResponse::new(list, items))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdateListResponses {
+    #[response(status = OK)]
+    Success(#[json] List),
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Partially update an existing list by id, owned by the logged in user
+#[utoipa::path(
+        patch,
+        path = "/lists/{id}",
+        params(
+            ("id", description = "List id")
+        ),
+        request_body = PartialList,
+        responses(UpdateListResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn update_list(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<i32>,
+    Json(data): Json<PartialList>,
+) -> Result<UpdateListResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(UpdateListResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match owned_list(&state.db, id, &user).await? {
+        Ok(_) => {}
+        Err(Denied::NotFound(problem)) => return Ok(UpdateListResponses::NotFound(problem)),
+        Err(Denied::Forbidden(problem)) => return Ok(UpdateListResponses::Forbidden(problem)),
+    }
+
+    match movies_core::update_list(&state.db, id, data).await {
+        Ok(list) => Ok(UpdateListResponses::Success(list)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(UpdateListResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeleteListResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing list by id, owned by the logged in user
+#[utoipa::path(
+        delete,
+        path = "/lists/{id}",
+        params(
+            ("id", description = "List id")
+        ),
+        responses(DeleteListResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn delete_list(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<i32>,
+) -> Result<DeleteListResponses, DatabaseErrorResponses> {
+    match owned_list(&state.db, id, &user).await? {
+        Ok(_) => {}
+        Err(Denied::NotFound(problem)) => return Ok(DeleteListResponses::NotFound(problem)),
+        Err(Denied::Forbidden(problem)) => return Ok(DeleteListResponses::Forbidden(problem)),
+    }
+
+    match movies_core::delete_list(&state.db, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => Ok(DeleteListResponses::Success),
+        Ok(_) => Ok(DeleteListResponses::NotFound(list_not_found(id))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum AddListItemResponses {
+    #[response(status = OK)]
+    Success(#[json] ListItem),
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Put a movie on an existing list by id, owned by the logged in user
+///
+/// A movie can only be on a list once.
+#[utoipa::path(
+        post,
+        path = "/lists/{id}/items",
+        params(
+            ("id", description = "List id")
+        ),
+        request_body = AddListItem,
+        responses(AddListItemResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn add_list_item(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<i32>,
+    Json(data): Json<AddListItem>,
+) -> Result<AddListItemResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(AddListItemResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match owned_list(&state.db, id, &user).await? {
+        Ok(_) => {}
+        Err(Denied::NotFound(problem)) => return Ok(AddListItemResponses::NotFound(problem)),
+        Err(Denied::Forbidden(problem)) => return Ok(AddListItemResponses::Forbidden(problem)),
+    }
+
+    match movies_core::add_list_item(&state.db, id, data).await {
+        Ok(item) => Ok(AddListItemResponses::Success(item)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(AddListItemResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdateListItemResponses {
+    #[response(status = OK)]
+    Success(#[json] ListItem),
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Change the note of a movie on an existing list by id, owned by the logged in user
+#[utoipa::path(
+        patch,
+        path = "/lists/{id}/items/{item_id}",
+        params(
+            ("id", description = "List id"),
+            ("item_id", description = "List item id")
+        ),
+        request_body = UpdateListItem,
+        responses(UpdateListItemResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn update_list_item(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, item_id)): Path<(i32, i32)>,
+    Json(data): Json<UpdateListItem>,
+) -> Result<UpdateListItemResponses, DatabaseErrorResponses> {
+    match owned_list(&state.db, id, &user).await? {
+        Ok(_) => {}
+        Err(Denied::NotFound(problem)) => return Ok(UpdateListItemResponses::NotFound(problem)),
+        Err(Denied::Forbidden(problem)) => return Ok(UpdateListItemResponses::Forbidden(problem)),
+    }
+
+    match movies_core::update_list_item(&state.db, id, item_id, data).await {
+        Ok(item) => Ok(UpdateListItemResponses::Success(item)),
+        Err(DbErr::RecordNotFound(message)) => Ok(UpdateListItemResponses::NotFound(
+            Problem::not_found(message),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum RemoveListItemResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Take a movie off an existing list by id, owned by the logged in user
+///
+/// The movies after it move up one position.
+#[utoipa::path(
+        delete,
+        path = "/lists/{id}/items/{item_id}",
+        params(
+            ("id", description = "List id"),
+            ("item_id", description = "List item id")
+        ),
+        responses(RemoveListItemResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn remove_list_item(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Path((id, item_id)): Path<(i32, i32)>,
+) -> Result<RemoveListItemResponses, DatabaseErrorResponses> {
+    match owned_list(&state.db, id, &user).await? {
+        Ok(_) => {}
+        Err(Denied::NotFound(problem)) => return Ok(RemoveListItemResponses::NotFound(problem)),
+        Err(Denied::Forbidden(problem)) => return Ok(RemoveListItemResponses::Forbidden(problem)),
+    }
+
+    match movies_core::remove_list_item(&state.db, id, item_id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(RemoveListItemResponses::Success)
+        }
+        Ok(_) => Ok(RemoveListItemResponses::NotFound(Problem::not_found(
+            format!("Item with id `{item_id}` not found on list `{id}`"),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ReorderListResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<ListItem>),
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Change the order of the movies on an existing list by id, owned by the logged in user
+#[utoipa::path(
+        put,
+        path = "/lists/{id}/order",
+        params(
+            ("id", description = "List id")
+        ),
+        request_body = ReorderList,
+        responses(ReorderListResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "lists"
+    )]
+async fn reorder_list(
+    state: State<ListsState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<i32>,
+    Json(data): Json<ReorderList>,
+) -> Result<ReorderListResponses, DatabaseErrorResponses> {
+    match owned_list(&state.db, id, &user).await? {
+        Ok(_) => {}
+        Err(Denied::NotFound(problem)) => return Ok(ReorderListResponses::NotFound(problem)),
+        Err(Denied::Forbidden(problem)) => return Ok(ReorderListResponses::Forbidden(problem)),
+    }
+
+    match movies_core::reorder_list(&state.db, id, data).await {
+        Ok(Some(items)) => Ok(ReorderListResponses::Success(items)),
+        Ok(None) => Ok(ReorderListResponses::UnprocessableEntity(
+            Problem::validation(vec![FieldError {
+                field: "item_ids".into(),
+                message: "must list the id of every item of the list once".into(),
+            }]),
+        )),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(ReorderListResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
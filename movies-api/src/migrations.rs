@@ -0,0 +1,84 @@
+use anyhow::{bail, Context};
+use movies_core::sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr, TransactionTrait,
+};
+use movies_migration::{Migrator, MigratorTrait};
+use tracing::{error, info, warn};
+
+use crate::config::MigrationMode;
+
+/// Key of the Postgres advisory lock held while applying migrations, "movies" in ASCII.
+const MIGRATION_LOCK_KEY: i64 = 0x6d6f76696573;
+
+/// Bring the database schema up to date, or make sure it is, according to `mode`.
+pub async fn run_migrations(db: &DatabaseConnection, mode: MigrationMode) -> anyhow::Result<()> {
+    match mode {
+        MigrationMode::Apply => {
+            let applied = apply_migrations(db)
+                .await
+                .context("Applying migrations failed")?;
+            if applied.is_empty() {
+                info!("Database schema is up to date");
+            } else {
+                info!(?applied, "Applied {} migrations", applied.len());
+            }
+        }
+        MigrationMode::Check => {
+            let pending = pending_migrations(db)
+                .await
+                .context("Checking migrations failed")?;
+            if !pending.is_empty() {
+                error!(?pending, "Database schema is out of date");
+                bail!(
+                    "{} pending migrations: {}, apply them or start with the `apply` mode",
+                    pending.len(),
+                    pending.join(", ")
+                );
+            }
+            info!("Database schema is up to date");
+        }
+        MigrationMode::Skip => warn!("Skipping migrations, the database schema may be out of date"),
+    }
+
+    Ok(())
+}
+
+async fn pending_migrations(db: &impl ConnectionTrait) -> Result<Vec<String>, DbErr> {
+    Ok(Migrator::get_pending_migrations(db)
+        .await?
+        .iter()
+        .map(|migration| migration.name().to_owned())
+        .collect())
+}
+
+/// Apply the pending migrations, returning their names.
+///
+/// On Postgres this holds an advisory lock until the migrations are committed, so that replicas
+/// starting at once apply them one after the other; the later ones then find nothing to apply.
+async fn apply_migrations(db: &DatabaseConnection) -> Result<Vec<String>, DbErr> {
+    if db.get_database_backend() != DatabaseBackend::Postgres {
+        let pending = pending_migrations(db).await?;
+        Migrator::up(db, None).await?;
+        return Ok(pending);
+    }
+
+    let transaction = db.begin().await?;
+
+    info!("Waiting for the migration lock");
+    transaction
+        .execute_unprepared(&format!(
+            "SELECT pg_advisory_xact_lock({MIGRATION_LOCK_KEY})"
+        ))
+        .await?;
+
+    let pending = pending_migrations(&transaction).await?;
+    if !pending.is_empty() {
+        info!(?pending, "Applying {} migrations", pending.len());
+        Migrator::up(&transaction, None).await?;
+    }
+
+    // Releases the lock as well
+    transaction.commit().await?;
+
+    Ok(pending)
+}
@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+use movies_macros::IntoResponse;
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use sqlx::PgPool;
+use tracing::{info_span, Instrument};
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::request_context::record_route;
+
+/// Counter of the requests handled, by method, route and status code.
+pub const HTTP_REQUESTS_METRIC: &str = "http_requests_total";
+/// Histogram of the time taken to handle requests, by method, route and status code.
+pub const HTTP_REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+/// Gauge of the connections in the database pool, by state.
+pub const DB_POOL_CONNECTIONS_METRIC: &str = "db_pool_connections";
+/// Gauge of the most connections the database pool may open.
+pub const DB_POOL_MAX_CONNECTIONS_METRIC: &str = "db_pool_max_connections";
+
+/// Bounds of the buckets of every `*_duration_seconds` histogram.
+const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(OpenApi)]
+#[openapi(paths(get_metrics))]
+pub struct MetricsApiDocs;
+
+/// Install the global metrics recorder, which can only be done once per process.
+pub fn install_recorder() -> Result<PrometheusHandle, BuildError> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_owned()),
+            DURATION_BUCKETS,
+        )?
+        .install_recorder()?;
+
+    // Histograms are only compacted on upkeep, which nothing else runs without the HTTP listener
+    // of the exporter
+    let upkeep_handle = handle.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        loop {
+            interval.tick().await;
+            upkeep_handle.run_upkeep();
+        }
+    });
+
+    Ok(handle)
+}
+
+pub fn metrics_routes(handle: PrometheusHandle, pool: PgPool) -> Router {
+    Router::new()
+        .route("/metrics", get(get_metrics))
+        .with_state(MetricsState { handle, pool })
+}
+
+#[derive(Clone)]
+struct MetricsState {
+    handle: PrometheusHandle,
+    pool: PgPool,
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum MetricsResponses {
+    #[response(status = OK, content_type = "text/plain")]
+    Success(String),
+}
+
+/// Get the metrics of the server in the Prometheus text format
+#[utoipa::path(get, path = "/metrics", responses(MetricsResponses), tag = "ops")]
+async fn get_metrics(state: State<MetricsState>) -> MetricsResponses {
+    record_pool_metrics(&state.pool);
+
+    MetricsResponses::Success(state.handle.render())
+}
+
+fn record_pool_metrics(pool: &PgPool) {
+    let idle = pool.num_idle() as f64;
+    gauge!(DB_POOL_CONNECTIONS_METRIC, "state" => "idle").set(idle);
+    gauge!(DB_POOL_CONNECTIONS_METRIC, "state" => "in_use").set(pool.size() as f64 - idle);
+    gauge!(DB_POOL_MAX_CONNECTIONS_METRIC).set(pool.options().get_max_connections() as f64);
+}
+
+/// Count and time requests, labelled by the route they matched rather than their path so that
+/// ids do not end up in labels.
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+    record_route(&method, &route);
+
+    let response = next
+        .run(request)
+        .instrument(info_span!("handler", otel.name = %route))
+        .await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!(HTTP_REQUESTS_METRIC, &labels).increment(1);
+    histogram!(HTTP_REQUEST_DURATION_METRIC, &labels).record(start.elapsed().as_secs_f64());
+
+    response
+}
@@ -1,16 +1,29 @@
 use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
-use movies_core::PartialMovie;
-use movies_entity::movie::Model as Movie;
+use movies_core::{
+    validate_ids, ChangeListener, CreateMovie, ExpandedMovie, FieldError, IncludedCredit,
+    MovieCredit, MovieCredits, MovieFilter, MovieIncludes, MovieResponse, MovieReviews, MovieSort,
+    Pagination, PartialMovie, RatingScale, RatingSummary, ReplaceMovie, SortOrder, TaxonomyFilter,
+    TaxonomyMatch, ValidateOnScale,
+};
+use movies_entity::genre::Model as Genre;
+use movies_entity::keyword::Model as Keyword;
+use movies_entity::review::Model as Review;
 use movies_macros::IntoResponse;
 use movies_migration::DbErr;
 
-use axum::extract::{Path, State};
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
 use axum::response::IntoResponse;
-use axum::routing::get;
-use axum::{Json, Router};
-use utoipa::{IntoResponses, OpenApi};
+use axum::routing::{get, put};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::{IntoParams, IntoResponses, OpenApi};
 
-use crate::responses::{database_error, ApiErrorBody};
+use crate::auth::{Authorized, Delete, Edit, View};
+use crate::extract::{Json, Path};
+use crate::pagination::{MoviesPage, Page, PaginationParams};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -21,13 +34,44 @@ use crate::responses::{database_error, ApiErrorBody};
         delete_movie,
         update_movie,
         patch_movie,
+        get_movie_credits,
+        assign_genre,
+        unassign_genre,
+        assign_keyword,
+        unassign_keyword,
     ),
-    components(schemas(Movie, PartialMovie, ApiErrorBody)),
+    components(schemas(
+        MovieResponse,
+        CreateMovie,
+        ReplaceMovie,
+        ExpandedMovie,
+        IncludedCredit,
+        MoviesPage,
+        PartialMovie,
+        SortOrder,
+        TaxonomyMatch,
+        Genre,
+        Keyword,
+        MovieCredit,
+        MovieCredits,
+        RatingSummary,
+        MovieReviews,
+        Review,
+        Problem,
+        ProblemCode,
+        FieldError
+    )),
     tags((name = "movies", description = "Rust Movies API"))
 )]
 pub struct MoviesApiDocs;
 
-pub fn movies_routes(db: DatabaseConnection) -> Router {
+/// Routes of movies, telling `changes` about the movies saved and deleted, and rating them on
+/// `ratings`.
+pub fn movies_routes(
+    db: DatabaseConnection,
+    changes: Arc<dyn ChangeListener>,
+    ratings: RatingScale,
+) -> Router {
     Router::new()
         .route("/", get(list_movies).post(create_movie))
         .route(
@@ -37,85 +81,384 @@ pub fn movies_routes(db: DatabaseConnection) -> Router {
                 .put(update_movie)
                 .patch(patch_movie),
         )
-        .with_state(MoviesState { db })
+        .route("/:id/credits", get(get_movie_credits))
+        .route(
+            "/:id/genres/:genre_id",
+            put(assign_genre).delete(unassign_genre),
+        )
+        .route(
+            "/:id/keywords/:keyword_id",
+            put(assign_keyword).delete(unassign_keyword),
+        )
+        .with_state(MoviesState {
+            db,
+            changes,
+            ratings,
+        })
 }
 
 #[derive(Clone)]
 struct MoviesState {
     db: DatabaseConnection,
+    changes: Arc<dyn ChangeListener>,
+    ratings: RatingScale,
+}
+
+impl FromRef<MoviesState> for DatabaseConnection {
+    fn from_ref(state: &MoviesState) -> Self {
+        state.db.clone()
+    }
 }
 
 #[derive(IntoResponse, IntoResponses)]
 enum ListMoviesResponses {
     #[response(status = OK)]
-    Success(#[json] Vec<Movie>),
+    Success(#[json] MoviesPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
 
-    #[response(status = INTERNAL_SERVER_ERROR)]
-    DatabaseError(#[json] ApiErrorBody),
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
 }
 
-/// Get all movies
-#[utoipa::path(get, path = "/movies", responses(ListMoviesResponses), tag = "movies")]
-async fn list_movies(state: State<MoviesState>) -> ListMoviesResponses {
-    match movies_core::get_all_movies(&state.db).await {
-        Ok(movies) => ListMoviesResponses::Success(movies),
-        Err(_) => ListMoviesResponses::DatabaseError(database_error()),
+/// Parse a comma separated list of related resources to embed in movies.
+fn parse_includes(include: Option<&str>) -> Result<MovieIncludes, String> {
+    let mut includes = MovieIncludes::default();
+
+    for name in include.into_iter().flat_map(|include| include.split(',')) {
+        match name.trim() {
+            "" => {}
+            "credits" => includes.credits = true,
+            "credits.person" => {
+                includes.credits = true;
+                includes.credit_persons = true;
+            }
+            "genres" => includes.genres = true,
+            "keywords" => includes.keywords = true,
+            other => return Err(format!("Unknown include `{other}`")),
+        }
+    }
+
+    Ok(includes)
+}
+
+/// Parse a comma separated list of genre or keyword ids to filter movies by.
+fn parse_taxonomy_filter(
+    field: &str,
+    ids: Option<&str>,
+    matching: Option<TaxonomyMatch>,
+) -> Result<Option<TaxonomyFilter>, String> {
+    let Some(ids) = ids.filter(|ids| !ids.is_empty()) else {
+        return Ok(None);
+    };
+
+    let ids = ids
+        .split(',')
+        .map(|id| {
+            id.trim()
+                .parse()
+                .map_err(|_| format!("`{field}` must be a comma separated list of ids, got `{id}`"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Some(TaxonomyFilter {
+        ids,
+        matching: matching.unwrap_or_default(),
+    }))
+}
+
+/// Related resources to embed in a movie
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct MovieIncludeParams {
+    /// Comma separated list of related resources to embed, among `credits`, `credits.person`,
+    /// `genres` and `keywords`
+    #[param(example = "credits,credits.person")]
+    include: Option<String>,
+}
+
+/// Filtering, sorting and embedding of the movie list
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct MovieListParams {
+    /// Only movies released at or after this date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    released_after: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only movies released at or before this date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    released_before: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only movies the editors rated at least this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_rating: Option<i32>,
+
+    /// Only movies the editors rated at most this
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_rating: Option<i32>,
+
+    /// Only movies whose title contains this, ignoring case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+
+    /// Only movies with these genres, as a comma separated list of ids
+    #[param(example = "1,4")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    genre: Option<String>,
+
+    /// Whether movies must have `any` of the genres, the default, or `all` of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    genre_match: Option<TaxonomyMatch>,
+
+    /// Only movies with these keywords, as a comma separated list of ids
+    #[param(example = "2,3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyword: Option<String>,
+
+    /// Whether movies must have `any` of the keywords, the default, or `all` of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyword_match: Option<TaxonomyMatch>,
+
+    /// Field to sort by, one of `id`, `title`, `release_date`, `poster_url`, `description` or
+    /// `rating`
+    #[param(example = "release_date")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sort: Option<String>,
+
+    /// Sort direction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<SortOrder>,
+
+    /// Comma separated list of related resources to embed, among `credits`, `credits.person`,
+    /// `genres` and `keywords`
+    #[param(example = "credits,credits.person")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    include: Option<String>,
+}
+
+impl MovieListParams {
+    fn to_filter_and_sort(&self) -> Result<(MovieFilter, MovieSort), String> {
+        let column = match &self.sort {
+            Some(sort) => sort
+                .parse()
+                .map_err(|_| format!("Unknown sort field `{sort}`"))?,
+            None => MovieSort::default().column,
+        };
+
+        let filter = MovieFilter {
+            released_after: self.released_after,
+            released_before: self.released_before,
+            min_rating: self.min_rating,
+            max_rating: self.max_rating,
+            title: self.title.clone().filter(|title| !title.is_empty()),
+            genres: parse_taxonomy_filter("genre", self.genre.as_deref(), self.genre_match)?,
+            keywords: parse_taxonomy_filter(
+                "keyword",
+                self.keyword.as_deref(),
+                self.keyword_match,
+            )?,
+        };
+
+        let sort = MovieSort {
+            column,
+            order: self.order.unwrap_or_default(),
+        };
+
+        Ok((filter, sort))
+    }
+}
+
+/// Get a page of movies
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/movies",
+    params(PaginationParams, MovieListParams),
+    responses(ListMoviesResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "movies"
+)]
+async fn list_movies(
+    state: State<MoviesState>,
+    _caller: Authorized<View>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+    params: Result<Query<MovieListParams>, QueryRejection>,
+) -> Result<ListMoviesResponses, DatabaseErrorResponses> {
+    let (Query(pagination), Query(params)) = match (pagination, params) {
+        (Ok(pagination), Ok(params)) => (pagination, params),
+        (Err(rejection), _) | (_, Err(rejection)) => {
+            return Ok(ListMoviesResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    let pagination: Pagination = match (&pagination).try_into() {
+        Ok(pagination) => pagination,
+        Err(message) => {
+            return Ok(ListMoviesResponses::BadRequest(Problem::bad_request(
+                message,
+            )))
+        }
+    };
+
+    let (filter, sort) = match params.to_filter_and_sort() {
+        Ok(filter_and_sort) => filter_and_sort,
+        Err(message) => {
+            return Ok(ListMoviesResponses::BadRequest(Problem::bad_request(
+                message,
+            )))
+        }
+    };
+
+    if let Err(errors) = filter.validate_on(&state.ratings) {
+        return Ok(ListMoviesResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    if !matches!(pagination, Pagination::Offset { .. }) && !sort.is_default() {
+        return Ok(ListMoviesResponses::BadRequest(Problem::bad_request(
+            "Cursor pagination does not support sorting, use `offset` instead",
+        )));
+    }
+
+    let includes = match parse_includes(params.include.as_deref()) {
+        Ok(includes) => includes,
+        Err(message) => {
+            return Ok(ListMoviesResponses::BadRequest(Problem::bad_request(
+                message,
+            )))
+        }
+    };
+
+    let mut page = match movies_core::get_all_movies(&state.db, &filter, sort, pagination).await {
+        Ok(page) => page,
+        Err(err) => return Err(err.into()),
+    };
+
+    let movies = std::mem::take(&mut page.items);
+
+    match movies_core::load_movie_includes(&state.db, movies, includes).await {
+        Ok(movies) => Ok(ListMoviesResponses::Success(Page::new(
+            page.with_items(movies),
+            "/movies",
+            &params,
+        ))),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[derive(IntoResponse, IntoResponses)]
 enum CreateMovieResponses {
     #[response(status = OK)]
-    Success(#[json] Movie),
+    Success(#[json] MovieResponse),
 
-    #[response(status = INTERNAL_SERVER_ERROR)]
-    DatabaseError(#[json] ApiErrorBody),
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
 }
 
 /// Create a movie
+///
+/// Needs the `editor` role.
 #[utoipa::path(
         post,
         path = "/movies",
-        request_body = Movie,
-        responses(CreateMovieResponses),
+        request_body = CreateMovie,
+        responses(CreateMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
         tag = "movies"
     )]
-async fn create_movie(state: State<MoviesState>, Json(data): Json<Movie>) -> CreateMovieResponses {
-    match movies_core::create_movie(&state.db, data).await {
-        Ok(created_movie) => CreateMovieResponses::Success(created_movie),
-        Err(_) => CreateMovieResponses::DatabaseError(database_error()),
+async fn create_movie(
+    state: State<MoviesState>,
+    _caller: Authorized<Edit>,
+    Json(data): Json<CreateMovie>,
+) -> Result<CreateMovieResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate_on(&state.ratings) {
+        return Ok(CreateMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::create_movie(&state.db, &*state.changes, data).await {
+        Ok(created_movie) => Ok(CreateMovieResponses::Success(created_movie.into())),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[derive(IntoResponse, IntoResponses)]
 enum GetMovieResponses {
     #[response(status = OK)]
-    Success(#[json] Movie),
+    Success(#[json] ExpandedMovie),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
 
-    #[response(status = NOT_FOUND)]
-    NotFound(#[json] ApiErrorBody),
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
 
-    #[response(status = INTERNAL_SERVER_ERROR)]
-    DatabaseError(#[json] ApiErrorBody),
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
 }
 
-/// Get an existing movie by id
+/// Get an existing movie by id, along with its latest published reviews
+///
+/// Needs the `viewer` role, which anonymous callers have.
 #[utoipa::path(
         get,
         path = "/movies/{id}",
         params(
-            ("id", description = "Movie id")
+            ("id", description = "Movie id"),
+            MovieIncludeParams
         ),
-        responses(GetMovieResponses),
+        responses(GetMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
         tag = "movies"
     )]
-async fn get_movie(state: State<MoviesState>, Path(id): Path<i32>) -> GetMovieResponses {
-    match movies_core::get_movie(&state.db, id).await {
-        Ok(Some(movie)) => GetMovieResponses::Success(movie),
-        Ok(None) => GetMovieResponses::NotFound(ApiErrorBody {
-            message: format!("Movie with id `{id}` not found"),
-        }),
-        Err(_) => GetMovieResponses::DatabaseError(database_error()),
+async fn get_movie(
+    state: State<MoviesState>,
+    _caller: Authorized<View>,
+    Path(id): Path<i32>,
+    params: Result<Query<MovieIncludeParams>, QueryRejection>,
+) -> Result<GetMovieResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id)]) {
+        return Ok(GetMovieResponses::UnprocessableEntity(Problem::validation(
+            errors,
+        )));
+    }
+
+    let includes = match params {
+        Ok(Query(params)) => parse_includes(params.include.as_deref()),
+        Err(rejection) => Err(rejection.body_text()),
+    };
+    let includes = match includes {
+        Ok(includes) => includes,
+        Err(message) => return Ok(GetMovieResponses::BadRequest(Problem::bad_request(message))),
+    };
+
+    let movie = match movies_core::get_movie(&state.db, id).await {
+        Ok(Some(movie)) => movie,
+        Ok(None) => {
+            return Ok(GetMovieResponses::NotFound(Problem::not_found(format!(
+                "Movie with id `{id}` not found"
+            ))))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut movie = match movies_core::load_movie_includes(&state.db, vec![movie], includes).await {
+        Ok(mut movies) => movies.remove(0),
+        Err(err) => return Err(err.into()),
+    };
+
+    match movies_core::get_movie_reviews(&state.db, id).await {
+        Ok(reviews) => {
+            movie.reviews = Some(reviews);
+            Ok(GetMovieResponses::Success(movie))
+        }
+        Err(err) => Err(err.into()),
     }
 }
 
@@ -124,71 +467,104 @@ enum DeleteMovieResponses {
     #[response(status = NO_CONTENT)]
     Success,
 
-    #[response(status = NOT_FOUND)]
-    NotFound(#[json] ApiErrorBody),
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
 
-    #[response(status = INTERNAL_SERVER_ERROR)]
-    DatabaseError(#[json] ApiErrorBody),
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
 }
 
 /// Delete an existing movie by id
+///
+/// Needs the `admin` role.
 #[utoipa::path(
         delete,
         path = "/movies/{id}",
         params(
             ("id", description = "Movie id")
         ),
-        responses(DeleteMovieResponses),
+        responses(DeleteMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
         tag = "movies"
     )]
-async fn delete_movie(state: State<MoviesState>, Path(id): Path<i32>) -> DeleteMovieResponses {
-    match movies_core::delete_movie(&state.db, id).await {
-        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => DeleteMovieResponses::Success,
-        Ok(_) => DeleteMovieResponses::NotFound(ApiErrorBody {
-            message: format!("Movie with id `{id}` not found"),
-        }),
-        Err(_) => DeleteMovieResponses::DatabaseError(database_error()),
+async fn delete_movie(
+    state: State<MoviesState>,
+    _caller: Authorized<Delete>,
+    Path(id): Path<i32>,
+) -> Result<DeleteMovieResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id)]) {
+        return Ok(DeleteMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::delete_movie(&state.db, &*state.changes, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(DeleteMovieResponses::Success)
+        }
+        Ok(_) => Ok(DeleteMovieResponses::NotFound(Problem::not_found(format!(
+            "Movie with id `{id}` not found"
+        )))),
+        Err(err) => Err(err.into()),
     }
 }
 
 #[derive(IntoResponse, IntoResponses)]
 enum UpdateMovieResponses {
     #[response(status = OK)]
-    Success(#[json] Movie),
+    Success(#[json] MovieResponse),
 
-    #[response(status = NOT_FOUND)]
-    NotFound(#[json] ApiErrorBody),
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
 
-    #[response(status = INTERNAL_SERVER_ERROR)]
-    DatabaseError(#[json] ApiErrorBody),
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
 }
 
 /// Update an existing movie by id
+///
+/// Needs the `editor` role.
 #[utoipa::path(
         put,
         path = "/movies/{id}",
         params(
             ("id", description = "Movie id")
         ),
-        request_body = Movie,
-        responses(UpdateMovieResponses),
+        request_body = ReplaceMovie,
+        responses(UpdateMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
         tag = "movies"
     )]
 async fn update_movie(
     state: State<MoviesState>,
+    _caller: Authorized<Edit>,
     Path(id): Path<i32>,
-    Json(data): Json<Movie>,
-) -> UpdateMovieResponses {
-    match movies_core::update_movie(&state.db, id, data).await {
-        Ok(movie) => UpdateMovieResponses::Success(movie),
+    Json(data): Json<ReplaceMovie>,
+) -> Result<UpdateMovieResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id)]) {
+        return Ok(UpdateMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    if let Err(errors) = data.validate_on(&state.ratings) {
+        return Ok(UpdateMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::update_movie(&state.db, &*state.changes, id, data).await {
+        Ok(movie) => Ok(UpdateMovieResponses::Success(movie.into())),
         Err(DbErr::RecordNotFound(message)) => {
-            UpdateMovieResponses::NotFound(ApiErrorBody { message })
+            Ok(UpdateMovieResponses::NotFound(Problem::not_found(message)))
         }
-        Err(_) => UpdateMovieResponses::DatabaseError(database_error()),
+        Err(err) => Err(err.into()),
     }
 }
 
 /// Partially update an existing movie by id
+///
+/// Needs the `editor` role.
 #[utoipa::path(
         patch,
         path = "/movies/{id}",
@@ -196,19 +572,238 @@ async fn update_movie(
             ("id", description = "Movie id")
         ),
         request_body = PartialMovie,
-        responses(UpdateMovieResponses),
+        responses(UpdateMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
         tag = "movies"
     )]
 async fn patch_movie(
     state: State<MoviesState>,
+    _caller: Authorized<Edit>,
     Path(id): Path<i32>,
     Json(data): Json<PartialMovie>,
-) -> UpdateMovieResponses {
-    match movies_core::update_movie_partial(&state.db, id, data).await {
-        Ok(movie) => UpdateMovieResponses::Success(movie),
+) -> Result<UpdateMovieResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id)]) {
+        return Ok(UpdateMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    if let Err(errors) = data.validate_on(&state.ratings) {
+        return Ok(UpdateMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::update_movie_partial(&state.db, &*state.changes, id, data).await {
+        Ok(movie) => Ok(UpdateMovieResponses::Success(movie.into())),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(UpdateMovieResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetMovieCreditsResponses {
+    #[response(status = OK)]
+    Success(#[json] MovieCredits),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Get the cast and crew of an existing movie by id
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+        get,
+        path = "/movies/{id}/credits",
+        params(
+            ("id", description = "Movie id")
+        ),
+        responses(GetMovieCreditsResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "movies"
+    )]
+async fn get_movie_credits(
+    state: State<MoviesState>,
+    _caller: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetMovieCreditsResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id)]) {
+        return Ok(GetMovieCreditsResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::get_movie_credits(&state.db, id).await {
+        Ok(Some(credits)) => Ok(GetMovieCreditsResponses::Success(credits)),
+        Ok(None) => Ok(GetMovieCreditsResponses::NotFound(Problem::not_found(
+            format!("Movie with id `{id}` not found"),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum AssignResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UnassignResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Assign an existing genre by id to an existing movie by id
+///
+/// Needs the `editor` role. Assigning a genre the movie already has does nothing.
+#[utoipa::path(
+        put,
+        path = "/movies/{id}/genres/{genre_id}",
+        params(
+            ("id", description = "Movie id"),
+            ("genre_id", description = "Genre id")
+        ),
+        responses(AssignResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "movies"
+    )]
+async fn assign_genre(
+    state: State<MoviesState>,
+    _caller: Authorized<Edit>,
+    Path((id, genre_id)): Path<(i32, i32)>,
+) -> Result<AssignResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id), ("genre_id", genre_id)]) {
+        return Ok(AssignResponses::UnprocessableEntity(Problem::validation(
+            errors,
+        )));
+    }
+
+    match movies_core::assign_genre(&state.db, id, genre_id).await {
+        Ok(()) => Ok(AssignResponses::Success),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(AssignResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Take an existing genre by id away from an existing movie by id
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        delete,
+        path = "/movies/{id}/genres/{genre_id}",
+        params(
+            ("id", description = "Movie id"),
+            ("genre_id", description = "Genre id")
+        ),
+        responses(UnassignResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "movies"
+    )]
+async fn unassign_genre(
+    state: State<MoviesState>,
+    _caller: Authorized<Edit>,
+    Path((id, genre_id)): Path<(i32, i32)>,
+) -> Result<UnassignResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id), ("genre_id", genre_id)]) {
+        return Ok(UnassignResponses::UnprocessableEntity(Problem::validation(
+            errors,
+        )));
+    }
+
+    match movies_core::unassign_genre(&state.db, id, genre_id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => Ok(UnassignResponses::Success),
+        Ok(_) => Ok(UnassignResponses::NotFound(Problem::not_found(format!(
+            "Movie with id `{id}` does not have the genre with id `{genre_id}`"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Assign an existing keyword by id to an existing movie by id
+///
+/// Needs the `editor` role. Assigning a keyword the movie already has does nothing.
+#[utoipa::path(
+        put,
+        path = "/movies/{id}/keywords/{keyword_id}",
+        params(
+            ("id", description = "Movie id"),
+            ("keyword_id", description = "Keyword id")
+        ),
+        responses(AssignResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "movies"
+    )]
+async fn assign_keyword(
+    state: State<MoviesState>,
+    _caller: Authorized<Edit>,
+    Path((id, keyword_id)): Path<(i32, i32)>,
+) -> Result<AssignResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id), ("keyword_id", keyword_id)]) {
+        return Ok(AssignResponses::UnprocessableEntity(Problem::validation(
+            errors,
+        )));
+    }
+
+    match movies_core::assign_keyword(&state.db, id, keyword_id).await {
+        Ok(()) => Ok(AssignResponses::Success),
         Err(DbErr::RecordNotFound(message)) => {
-            UpdateMovieResponses::NotFound(ApiErrorBody { message })
+            Ok(AssignResponses::NotFound(Problem::not_found(message)))
         }
-        Err(_) => UpdateMovieResponses::DatabaseError(database_error()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Take an existing keyword by id away from an existing movie by id
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        delete,
+        path = "/movies/{id}/keywords/{keyword_id}",
+        params(
+            ("id", description = "Movie id"),
+            ("keyword_id", description = "Keyword id")
+        ),
+        responses(UnassignResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "movies"
+    )]
+async fn unassign_keyword(
+    state: State<MoviesState>,
+    _caller: Authorized<Edit>,
+    Path((id, keyword_id)): Path<(i32, i32)>,
+) -> Result<UnassignResponses, DatabaseErrorResponses> {
+    if let Err(errors) = validate_ids(&[("id", id), ("keyword_id", keyword_id)]) {
+        return Ok(UnassignResponses::UnprocessableEntity(Problem::validation(
+            errors,
+        )));
+    }
+
+    match movies_core::unassign_keyword(&state.db, id, keyword_id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => Ok(UnassignResponses::Success),
+        Ok(_) => Ok(UnassignResponses::NotFound(Problem::not_found(format!(
+            "Movie with id `{id}` does not have the keyword with id `{keyword_id}`"
+        )))),
+        Err(err) => Err(err.into()),
     }
 }
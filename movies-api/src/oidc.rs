@@ -0,0 +1,359 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use movies_core::OidcIdentity;
+use movies_entity::sea_orm_active_enums::UserRole;
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::config::OidcConfig;
+
+/// Seconds of clock skew with the provider tolerated when checking ID tokens.
+const CLOCK_SKEW_LEEWAY_SECS: u64 = 60;
+
+/// Why logging in with the provider failed.
+#[derive(Debug)]
+pub enum OidcError {
+    /// The provider could not be reached or answered unexpectedly
+    Provider(String),
+    /// The provider refused the login, or gave an ID token that cannot be trusted
+    Rejected(String),
+}
+
+impl fmt::Display for OidcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OidcError::Provider(message) => write!(f, "identity provider error: {message}"),
+            OidcError::Rejected(message) => write!(f, "login rejected: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for OidcError {}
+
+fn provider_error(context: &str) -> impl FnOnce(reqwest::Error) -> OidcError + '_ {
+    move |err| OidcError::Provider(format!("{context}: {err}"))
+}
+
+/// The parts of the discovery document of the provider used here.
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug)]
+struct Provider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+}
+
+/// Secrets of a login in progress, kept by the browser between leaving for the provider and
+/// coming back to the callback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginState {
+    /// Binds the callback to the browser that started the login
+    pub state: String,
+    /// Binds the ID token to this login
+    pub nonce: String,
+    /// PKCE verifier, whose hash was sent to the provider
+    pub code_verifier: String,
+}
+
+impl LoginState {
+    fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+
+        LoginState {
+            state: Alphanumeric.sample_string(&mut rng, 32),
+            nonce: Alphanumeric.sample_string(&mut rng, 32),
+            code_verifier: Alphanumeric.sample_string(&mut rng, 64),
+        }
+    }
+
+    fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+}
+
+/// Claims of an ID token used to identify the user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+    /// Every other claim, among which the role claim
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Client of an OpenID Connect provider, logging users in with the authorization code flow and
+/// PKCE.
+///
+/// The discovery document and signing keys of the provider are fetched on first use and kept,
+/// the keys being fetched again when an ID token is signed with an unknown one.
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    provider: RwLock<Option<Arc<Provider>>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("the TLS backend is available");
+
+        OidcClient {
+            config,
+            http,
+            provider: RwLock::new(None),
+        }
+    }
+
+    /// Start a login, returning the URL of the provider to send the user to and the state to
+    /// check the callback against.
+    pub async fn start_login(&self) -> Result<(Url, LoginState), OidcError> {
+        let provider = self.provider(false).await?;
+        let login = LoginState::generate();
+
+        let mut url = Url::parse(&provider.metadata.authorization_endpoint)
+            .map_err(|err| OidcError::Provider(format!("invalid authorization endpoint: {err}")))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", &login.state)
+            .append_pair("nonce", &login.nonce)
+            .append_pair("code_challenge", &login.code_challenge())
+            .append_pair("code_challenge_method", "S256");
+
+        Ok((url, login))
+    }
+
+    /// Exchange the authorization code given to the callback for an ID token, and check it.
+    pub async fn finish_login(
+        &self,
+        code: &str,
+        login: &LoginState,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let provider = self.provider(false).await?;
+
+        let mut request = self.http.post(&provider.metadata.token_endpoint).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_url),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", &login.code_verifier),
+        ]);
+        if let Some(secret) = &self.config.client_secret {
+            request = request.basic_auth(&self.config.client_id, Some(secret));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(provider_error("token request failed"))?;
+        let status = response.status();
+        if status.is_client_error() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(OidcError::Rejected(format!(
+                "the provider refused the authorization code ({status}): {body}"
+            )));
+        }
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(provider_error("token request failed"))?
+            .json()
+            .await
+            .map_err(provider_error("invalid token response"))?;
+
+        self.validate_id_token(&tokens.id_token, &login.nonce).await
+    }
+
+    async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let rejected = |message: String| OidcError::Rejected(message);
+
+        let header = jsonwebtoken::decode_header(id_token)
+            .map_err(|err| rejected(format!("malformed ID token: {err}")))?;
+        // Only asymmetric algorithms, as the keys come from the JWKS of the provider
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(rejected(format!(
+                "ID token signed with the unsupported {:?} algorithm",
+                header.alg
+            )));
+        }
+
+        let mut provider = self.provider(false).await?;
+        let find_key = |provider: &Provider| match &header.kid {
+            Some(kid) => provider.jwks.find(kid).cloned(),
+            None => provider.jwks.keys.first().cloned(),
+        };
+        let jwk = match find_key(&provider) {
+            Some(jwk) => jwk,
+            None => {
+                // The provider may have rotated its keys
+                provider = self.provider(true).await?;
+                find_key(&provider)
+                    .ok_or_else(|| rejected("ID token signed with an unknown key".to_owned()))?
+            }
+        };
+        let key = DecodingKey::from_jwk(&jwk)
+            .map_err(|err| OidcError::Provider(format!("unusable signing key: {err}")))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = CLOCK_SKEW_LEEWAY_SECS;
+
+        let claims = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|err| rejected(format!("invalid ID token: {err}")))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(rejected("ID token issued for another login".to_owned()));
+        }
+
+        Ok(claims)
+    }
+
+    /// The local identity of the user of an ID token.
+    pub fn identity(&self, claims: IdTokenClaims) -> Result<OidcIdentity, OidcError> {
+        let role = self.role(&claims);
+        let email = claims
+            .email
+            .ok_or_else(|| OidcError::Rejected("the ID token has no `email` claim".to_owned()))?;
+        let display_name = claims
+            .name
+            .or(claims.preferred_username)
+            .unwrap_or_else(|| email.clone());
+
+        Ok(OidcIdentity {
+            issuer: claims.iss,
+            subject: claims.sub,
+            email,
+            email_verified: claims.email_verified,
+            display_name,
+            role,
+        })
+    }
+
+    /// Role given by the role claim, `None` if no role claim is configured.
+    fn role(&self, claims: &IdTokenClaims) -> Option<UserRole> {
+        let path = self.config.role_claim.as_deref()?;
+
+        let mut segments = path.split('.');
+        let first = segments.next().and_then(|name| claims.other.get(name));
+        let claim = segments.fold(first, |claim, name| claim?.get(name));
+
+        let values: Vec<&str> = match claim {
+            Some(Value::String(value)) => vec![value],
+            Some(Value::Array(values)) => values.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        let has_any = |roles: &[String]| {
+            values
+                .iter()
+                .any(|value| roles.iter().any(|role| role == value))
+        };
+
+        Some(if has_any(&self.config.admin_roles) {
+            UserRole::Admin
+        } else if has_any(&self.config.editor_roles) {
+            UserRole::Editor
+        } else {
+            UserRole::Viewer
+        })
+    }
+
+    /// Discovery document and signing keys of the provider, fetched if not yet known or if
+    /// `refresh` is set.
+    async fn provider(&self, refresh: bool) -> Result<Arc<Provider>, OidcError> {
+        if !refresh {
+            if let Some(provider) = self.provider.read().await.as_ref() {
+                return Ok(provider.clone());
+            }
+        }
+
+        let mut cached = self.provider.write().await;
+        // Another request may have fetched them while waiting for the lock
+        if let (false, Some(provider)) = (refresh, cached.as_ref()) {
+            return Ok(provider.clone());
+        }
+
+        let metadata = match cached.as_ref() {
+            Some(provider) => provider.metadata.clone(),
+            None => self.discover().await?,
+        };
+        let jwks = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(provider_error("fetching the signing keys failed"))?
+            .json()
+            .await
+            .map_err(provider_error("invalid signing keys"))?;
+
+        let provider = Arc::new(Provider { metadata, jwks });
+        *cached = Some(provider.clone());
+
+        Ok(provider)
+    }
+
+    async fn discover(&self) -> Result<ProviderMetadata, OidcError> {
+        let issuer_url = self.config.issuer_url.trim_end_matches('/');
+
+        let metadata: ProviderMetadata = self
+            .http
+            .get(format!("{issuer_url}/.well-known/openid-configuration"))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(provider_error("discovery failed"))?
+            .json()
+            .await
+            .map_err(provider_error("invalid discovery document"))?;
+
+        if metadata.issuer.trim_end_matches('/') != issuer_url {
+            return Err(OidcError::Provider(format!(
+                "the discovery document is for the issuer `{}`",
+                metadata.issuer
+            )));
+        }
+
+        Ok(metadata)
+    }
+}
@@ -0,0 +1,100 @@
+use movies_core::sea_orm::DatabaseConnection;
+use movies_macros::IntoResponse;
+use movies_migration::{Migrator, MigratorTrait};
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::Serialize;
+use tracing::warn;
+use utoipa::{IntoResponses, OpenApi, ToSchema};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(healthz, readyz),
+    components(schemas(Health, Readiness)),
+    tags((name = "ops", description = "Probes for orchestrators and load balancers"))
+)]
+pub struct OpsApiDocs;
+
+pub fn ops_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(OpsState { db })
+}
+
+#[derive(Clone)]
+struct OpsState {
+    db: DatabaseConnection,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Health {
+    #[schema(example = "ok")]
+    status: &'static str,
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum HealthResponses {
+    #[response(status = OK)]
+    Success(#[json] Health),
+}
+
+/// Check that the server is up
+#[utoipa::path(get, path = "/healthz", responses(HealthResponses), tag = "ops")]
+async fn healthz() -> HealthResponses {
+    HealthResponses::Success(Health { status: "ok" })
+}
+
+/// Whether the server can handle requests.
+#[derive(Serialize, ToSchema)]
+pub struct Readiness {
+    /// Whether the database answers
+    database: bool,
+    /// Migrations yet to be applied to the database
+    pending_migrations: Vec<String>,
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ReadinessResponses {
+    #[response(status = OK)]
+    Ready(#[json] Readiness),
+
+    #[response(status = SERVICE_UNAVAILABLE)]
+    NotReady(#[json] Readiness),
+}
+
+/// Check that the database answers and its schema is up to date
+#[utoipa::path(get, path = "/readyz", responses(ReadinessResponses), tag = "ops")]
+async fn readyz(state: State<OpsState>) -> ReadinessResponses {
+    if let Err(err) = state.db.ping().await {
+        warn!("Not ready, the database does not answer: {err}");
+        return ReadinessResponses::NotReady(Readiness {
+            database: false,
+            pending_migrations: vec![],
+        });
+    }
+
+    match Migrator::get_pending_migrations(&state.db).await {
+        Ok(pending) if pending.is_empty() => ReadinessResponses::Ready(Readiness {
+            database: true,
+            pending_migrations: vec![],
+        }),
+        Ok(pending) => ReadinessResponses::NotReady(Readiness {
+            database: true,
+            pending_migrations: pending
+                .iter()
+                .map(|migration| migration.name().to_owned())
+                .collect(),
+        }),
+        Err(err) => {
+            warn!("Not ready, cannot list pending migrations: {err}");
+            ReadinessResponses::NotReady(Readiness {
+                database: false,
+                pending_migrations: vec![],
+            })
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use movies_core::{ExpandedMovie, Pagination, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use movies_entity::genre::Model as Genre;
+use movies_entity::keyword::Model as Keyword;
+use movies_entity::list::Model as List;
+use movies_entity::person::Model as Person;
+use movies_entity::review::Model as Review;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// Query parameters selecting a page of a list.
+///
+/// Use either `offset`, or one of `after` / `before` for cursor pagination on the item id.
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaginationParams {
+    /// Maximum number of items in the page
+    #[param(minimum = 1, maximum = 100, default = 20)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+
+    /// Number of items to skip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+
+    /// Only return items with an id greater than this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<i32>,
+
+    /// Only return items with an id lower than this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<i32>,
+}
+
+impl TryFrom<&PaginationParams> for Pagination {
+    type Error = String;
+
+    fn try_from(params: &PaginationParams) -> Result<Self, Self::Error> {
+        let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(format!("`limit` must be between 1 and {MAX_PAGE_SIZE}"));
+        }
+
+        match (params.offset, params.after, params.before) {
+            (offset, None, None) => Ok(Pagination::Offset {
+                limit,
+                offset: offset.unwrap_or(0),
+            }),
+            (None, Some(id), None) => Ok(Pagination::After { limit, id }),
+            (None, None, Some(id)) => Ok(Pagination::Before { limit, id }),
+            _ => Err("At most one of `offset`, `after` and `before` may be given".into()),
+        }
+    }
+}
+
+impl From<Pagination> for PaginationParams {
+    fn from(pagination: Pagination) -> Self {
+        match pagination {
+            Pagination::Offset { limit, offset } => PaginationParams {
+                limit: Some(limit),
+                offset: Some(offset),
+                ..Default::default()
+            },
+            Pagination::After { limit, id } => PaginationParams {
+                limit: Some(limit),
+                after: Some(id),
+                ..Default::default()
+            },
+            Pagination::Before { limit, id } => PaginationParams {
+                limit: Some(limit),
+                before: Some(id),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// A page of items, with links to the neighbouring pages.
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(
+    MoviesPage = Page<ExpandedMovie>,
+    PersonsPage = Page<Person>,
+    ListsPage = Page<List>,
+    ReviewsPage = Page<Review>,
+    GenresPage = Page<Genre>,
+    KeywordsPage = Page<Keyword>
+)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Total number of items across all pages
+    pub total: u64,
+    /// Maximum number of items in this page
+    pub page_size: u64,
+    /// Link to the next page, if any
+    pub next: Option<String>,
+    /// Link to the previous page, if any
+    pub prev: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Build the response body for `page`, linking to `path` with the given extra query
+    /// parameters preserved in the links.
+    pub fn new(page: movies_core::Page<T>, path: &str, query: &impl Serialize) -> Self {
+        let link = |pagination: Pagination| {
+            let mut parts = vec![];
+
+            for part in [
+                serde_urlencoded::to_string(query),
+                serde_urlencoded::to_string(PaginationParams::from(pagination)),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if !part.is_empty() {
+                    parts.push(part);
+                }
+            }
+
+            format!("{path}?{}", parts.join("&"))
+        };
+
+        Page {
+            next: page.next.map(link),
+            prev: page.prev.map(link),
+            items: page.items,
+            total: page.total,
+            page_size: page.page_size,
+        }
+    }
+}
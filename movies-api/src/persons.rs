@@ -0,0 +1,351 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_core::{
+    ChangeListener, FieldError, FilmographyEntry, Pagination, PartialPerson, Validate,
+};
+use movies_entity::person::Model as Person;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use std::sync::Arc;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, Delete, Edit, View};
+use crate::extract::{Json, Path};
+use crate::pagination::{Page, PaginationParams, PersonsPage};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_persons,
+        create_person,
+        get_person,
+        delete_person,
+        update_person,
+        patch_person,
+        get_person_filmography,
+    ),
+    components(schemas(
+        Person,
+        PersonsPage,
+        PartialPerson,
+        FilmographyEntry,
+        Problem,
+        ProblemCode,
+        FieldError
+    )),
+    tags((name = "persons", description = "Rust Movies API"))
+)]
+pub struct PersonsApiDocs;
+
+/// Routes of persons, telling `changes` about the persons saved and deleted.
+pub fn persons_routes(db: DatabaseConnection, changes: Arc<dyn ChangeListener>) -> Router {
+    Router::new()
+        .route("/", get(list_persons).post(create_person))
+        .route(
+            "/:id",
+            get(get_person)
+                .delete(delete_person)
+                .put(update_person)
+                .patch(patch_person),
+        )
+        .route("/:id/filmography", get(get_person_filmography))
+        .with_state(PersonsState { db, changes })
+}
+
+#[derive(Clone)]
+struct PersonsState {
+    db: DatabaseConnection,
+    changes: Arc<dyn ChangeListener>,
+}
+
+impl FromRef<PersonsState> for DatabaseConnection {
+    fn from_ref(state: &PersonsState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListPersonsResponses {
+    #[response(status = OK)]
+    Success(#[json] PersonsPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Get a page of persons
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/persons",
+    params(PaginationParams),
+    responses(ListPersonsResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "persons"
+)]
+async fn list_persons(
+    state: State<PersonsState>,
+    _caller: Authorized<View>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+) -> Result<ListPersonsResponses, DatabaseErrorResponses> {
+    let pagination: Pagination = match pagination {
+        Ok(Query(pagination)) => match (&pagination).try_into() {
+            Ok(pagination) => pagination,
+            Err(message) => {
+                return Ok(ListPersonsResponses::BadRequest(Problem::bad_request(
+                    message,
+                )))
+            }
+        },
+        Err(rejection) => {
+            return Ok(ListPersonsResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    match movies_core::get_all_persons(&state.db, pagination).await {
+        Ok(page) => Ok(ListPersonsResponses::Success(Page::new(
+            page,
+            "/persons",
+            &(),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum CreatePersonResponses {
+    #[response(status = OK)]
+    Success(#[json] Person),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Create a person
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        post,
+        path = "/persons",
+        request_body = Person,
+        responses(CreatePersonResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "persons"
+    )]
+async fn create_person(
+    state: State<PersonsState>,
+    _caller: Authorized<Edit>,
+    Json(data): Json<Person>,
+) -> Result<CreatePersonResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(CreatePersonResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::create_person(&state.db, &*state.changes, data).await {
+        Ok(created_person) => Ok(CreatePersonResponses::Success(created_person)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetPersonResponses {
+    #[response(status = OK)]
+    Success(#[json] Person),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Get an existing person by id
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+        get,
+        path = "/persons/{id}",
+        params(
+            ("id", description = "Person id")
+        ),
+        responses(GetPersonResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "persons"
+    )]
+async fn get_person(
+    state: State<PersonsState>,
+    _caller: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetPersonResponses, DatabaseErrorResponses> {
+    match movies_core::get_person(&state.db, id).await {
+        Ok(Some(person)) => Ok(GetPersonResponses::Success(person)),
+        Ok(None) => Ok(GetPersonResponses::NotFound(Problem::not_found(format!(
+            "Person with id `{id}` not found"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeletePersonResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing person by id
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+        delete,
+        path = "/persons/{id}",
+        params(
+            ("id", description = "Person id")
+        ),
+        responses(DeletePersonResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "persons"
+    )]
+async fn delete_person(
+    state: State<PersonsState>,
+    _caller: Authorized<Delete>,
+    Path(id): Path<i32>,
+) -> Result<DeletePersonResponses, DatabaseErrorResponses> {
+    match movies_core::delete_person(&state.db, &*state.changes, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(DeletePersonResponses::Success)
+        }
+        Ok(_) => Ok(DeletePersonResponses::NotFound(Problem::not_found(
+            format!("Person with id `{id}` not found"),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdatePersonResponses {
+    #[response(status = OK)]
+    Success(#[json] Person),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Update an existing person by id
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        put,
+        path = "/persons/{id}",
+        params(
+            ("id", description = "Person id")
+        ),
+        request_body = Person,
+        responses(UpdatePersonResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "persons"
+    )]
+async fn update_person(
+    state: State<PersonsState>,
+    _caller: Authorized<Edit>,
+    Path(id): Path<i32>,
+    Json(data): Json<Person>,
+) -> Result<UpdatePersonResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(UpdatePersonResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::update_person(&state.db, &*state.changes, id, data).await {
+        Ok(person) => Ok(UpdatePersonResponses::Success(person)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(UpdatePersonResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Partially update an existing person by id
+///
+/// Needs the `editor` role.
+#[utoipa::path(
+        patch,
+        path = "/persons/{id}",
+        params(
+            ("id", description = "Person id")
+        ),
+        request_body = PartialPerson,
+        responses(UpdatePersonResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "persons"
+    )]
+async fn patch_person(
+    state: State<PersonsState>,
+    _caller: Authorized<Edit>,
+    Path(id): Path<i32>,
+    Json(data): Json<PartialPerson>,
+) -> Result<UpdatePersonResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(UpdatePersonResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::update_person_partial(&state.db, &*state.changes, id, data).await {
+        Ok(person) => Ok(UpdatePersonResponses::Success(person)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(UpdatePersonResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetPersonFilmographyResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<FilmographyEntry>),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Get the movies an existing person by id is credited on
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+        get,
+        path = "/persons/{id}/filmography",
+        params(
+            ("id", description = "Person id")
+        ),
+        responses(GetPersonFilmographyResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "persons"
+    )]
+async fn get_person_filmography(
+    state: State<PersonsState>,
+    _caller: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetPersonFilmographyResponses, DatabaseErrorResponses> {
+    match movies_core::get_person_filmography(&state.db, id).await {
+        Ok(Some(filmography)) => Ok(GetPersonFilmographyResponses::Success(filmography)),
+        Ok(None) => Ok(GetPersonFilmographyResponses::NotFound(Problem::not_found(
+            format!("Person with id `{id}` not found"),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,158 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_core::{FieldError, RateMovie, RatingScale, RatingSummary, ValidateOnScale};
+use movies_entity::user_rating::Model as UserRating;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::{FromRef, State};
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::Router;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::CurrentUser;
+use crate::extract::{Json, Path};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_ratings, rate_movie, unrate_movie),
+    components(schemas(
+        UserRating,
+        RateMovie,
+        RatingSummary,
+        Problem,
+        ProblemCode,
+        FieldError
+    )),
+    tags((
+        name = "ratings",
+        description = "Scores users give movies, summed up in the `user_ratings` of each movie"
+    ))
+)]
+pub struct RatingsApiDocs;
+
+/// Routes of ratings, scored on `ratings`.
+pub fn ratings_routes(db: DatabaseConnection, ratings: RatingScale) -> Router {
+    Router::new()
+        .route("/", get(list_ratings))
+        .route("/:movie_id", put(rate_movie).delete(unrate_movie))
+        .with_state(RatingsState { db, ratings })
+}
+
+#[derive(Clone)]
+struct RatingsState {
+    db: DatabaseConnection,
+    ratings: RatingScale,
+}
+
+impl FromRef<RatingsState> for DatabaseConnection {
+    fn from_ref(state: &RatingsState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListRatingsResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<UserRating>),
+}
+
+/// Get the ratings of the logged in user, most recent first
+#[utoipa::path(
+        get,
+        path = "/ratings",
+        responses(ListRatingsResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "ratings"
+    )]
+async fn list_ratings(
+    state: State<RatingsState>,
+    CurrentUser(user): CurrentUser,
+) -> Result<ListRatingsResponses, DatabaseErrorResponses> {
+    match movies_core::get_user_ratings(&state.db, user.id).await {
+        Ok(ratings) => Ok(ListRatingsResponses::Success(ratings)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum RateMovieResponses {
+    #[response(status = OK)]
+    Success(#[json] UserRating),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Rate an existing movie by id as the logged in user, replacing their previous score
+#[utoipa::path(
+        put,
+        path = "/ratings/{movie_id}",
+        params(
+            ("movie_id", description = "Movie id")
+        ),
+        request_body = RateMovie,
+        responses(RateMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "ratings"
+    )]
+async fn rate_movie(
+    state: State<RatingsState>,
+    CurrentUser(user): CurrentUser,
+    Path(movie_id): Path<i32>,
+    Json(data): Json<RateMovie>,
+) -> Result<RateMovieResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate_on(&state.ratings) {
+        return Ok(RateMovieResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::rate_movie(&state.db, user.id, movie_id, data.score).await {
+        Ok(rating) => Ok(RateMovieResponses::Success(rating)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(RateMovieResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UnrateMovieResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Remove the rating the logged in user gave an existing movie by id
+#[utoipa::path(
+        delete,
+        path = "/ratings/{movie_id}",
+        params(
+            ("movie_id", description = "Movie id")
+        ),
+        responses(UnrateMovieResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "ratings"
+    )]
+async fn unrate_movie(
+    state: State<RatingsState>,
+    CurrentUser(user): CurrentUser,
+    Path(movie_id): Path<i32>,
+) -> Result<UnrateMovieResponses, DatabaseErrorResponses> {
+    match movies_core::unrate_movie(&state.db, user.id, movie_id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(UnrateMovieResponses::Success)
+        }
+        Ok(_) => Ok(UnrateMovieResponses::NotFound(Problem::not_found(format!(
+            "The movie with id `{movie_id}` is not rated"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
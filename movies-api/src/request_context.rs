@@ -0,0 +1,141 @@
+use std::time::Instant;
+
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::field::Empty;
+use tracing::{info, info_span, Instrument, Span};
+use uuid::Uuid;
+
+/// Header carrying the id of a request, from the client or generated, and echoed in the response.
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest request id accepted from clients, longer ones are replaced with a generated one.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id correlating the logs, response and problem details of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Take the id given by the client if it is reasonable, else generate one.
+    fn from_request(request: &Request) -> Self {
+        let given = request
+            .headers()
+            .get(&X_REQUEST_ID)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LEN
+                    && id.bytes().all(|byte| byte.is_ascii_graphic())
+            });
+
+        match given {
+            Some(id) => RequestId(id.to_owned()),
+            None => RequestId(Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// Give every request an id and handle it in its own span, logging its outcome when done.
+///
+/// The `route` field of the span is recorded by `monitoring::track_requests`, as the matched route
+/// is only known once the request is routed.
+pub async fn request_context(mut request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let request_id = RequestId::from_request(&request);
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id.0,
+        method = %request.method(),
+        route = Empty,
+        status = Empty,
+        latency_ms = Empty,
+        otel.kind = "server",
+        otel.status_code = Empty,
+    );
+    #[cfg(feature = "otel")]
+    crate::otel::set_parent(&span, request.headers());
+
+    request.extensions_mut().insert(request_id.clone());
+
+    let mut response = next.run(request).instrument(span.clone()).await;
+
+    span.record("status", response.status().as_u16());
+    span.record("latency_ms", start.elapsed().as_secs_f64() * 1000.0);
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+    span.in_scope(|| info!("Finished request"));
+
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+
+    response
+}
+
+/// Record the route matched by the current request on its span, which is named after it in traces.
+pub fn record_route(method: &str, route: &str) {
+    let span = Span::current();
+    span.record("route", route);
+
+    #[cfg(feature = "otel")]
+    crate::otel::rename(&span, format!("{method} {route}"));
+    #[cfg(not(feature = "otel"))]
+    let _ = method;
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+
+    use super::*;
+
+    fn request_id(header: Option<&[u8]>) -> RequestId {
+        let mut request = Request::new(Body::empty());
+        if let Some(header) = header {
+            let value = HeaderValue::from_bytes(header).unwrap();
+            request.headers_mut().insert(&X_REQUEST_ID, value);
+        }
+
+        RequestId::from_request(&request)
+    }
+
+    fn is_generated(RequestId(id): &RequestId) -> bool {
+        Uuid::parse_str(id).is_ok()
+    }
+
+    #[test]
+    fn accept_reasonable_ids_from_clients() {
+        let longest = "a".repeat(MAX_REQUEST_ID_LEN);
+
+        let valid = request_id(Some(b"req-42_abc.DEF:/~"));
+        let longest_valid = request_id(Some(longest.as_bytes()));
+
+        assert_eq!(valid, RequestId("req-42_abc.DEF:/~".to_owned()));
+        assert_eq!(longest_valid, RequestId(longest));
+    }
+
+    #[test]
+    fn replace_missing_or_unreasonable_ids() {
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+
+        let ids = [
+            request_id(None),
+            request_id(Some(b"")),
+            request_id(Some(too_long.as_bytes())),
+            request_id(Some(b"with space")),
+            request_id(Some(b"tab\tinside")),
+            request_id(Some("caf\u{e9}".as_bytes())),
+            request_id(Some(b"\x80\xff")),
+        ];
+
+        for id in &ids {
+            assert!(is_generated(id), "{id:?} was not generated");
+        }
+        assert_ne!(ids[0], request_id(None));
+    }
+}
@@ -1,13 +1,273 @@
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use movies_core::sea_orm::{DbErr, RuntimeErr, SqlErr};
+use movies_core::FieldError;
+use movies_macros::IntoResponse;
 use serde::Serialize;
-use utoipa::ToSchema;
+use tracing::{error, warn};
+use utoipa::{IntoResponses, ToSchema};
 
-#[derive(Debug, Serialize, ToSchema)]
-pub struct ApiErrorBody {
-    pub message: String,
+use crate::request_context::RequestId;
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Machine-readable identifier of a kind of problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemCode {
+    BadRequest,
+    UnsupportedMediaType,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    ValidationFailed,
+    InvalidTransition,
+    UniqueViolation,
+    ForeignKeyViolation,
+    CheckViolation,
+    DatabaseUnavailable,
+    DatabaseError,
+    SearchIndexError,
+    IdentityProviderError,
+    InternalError,
 }
 
-pub fn database_error() -> ApiErrorBody {
-    ApiErrorBody {
-        message: "Database error".into(),
+impl ProblemCode {
+    fn title(&self) -> &'static str {
+        match self {
+            ProblemCode::BadRequest => "Bad request",
+            ProblemCode::UnsupportedMediaType => "Unsupported media type",
+            ProblemCode::Unauthorized => "Authentication required",
+            ProblemCode::Forbidden => "Permission denied",
+            ProblemCode::NotFound => "Resource not found",
+            ProblemCode::ValidationFailed => "Invalid input",
+            ProblemCode::InvalidTransition => "Invalid state transition",
+            ProblemCode::UniqueViolation => "Resource already exists",
+            ProblemCode::ForeignKeyViolation => "Related resource does not exist",
+            ProblemCode::CheckViolation => "Invalid input",
+            ProblemCode::DatabaseUnavailable => "Database unavailable",
+            ProblemCode::DatabaseError => "Database error",
+            ProblemCode::SearchIndexError => "Search index error",
+            ProblemCode::IdentityProviderError => "Identity provider error",
+            ProblemCode::InternalError => "Internal error",
+        }
     }
 }
+
+/// Problem details as specified by RFC 7807, served as `application/problem+json`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Problem {
+    /// URI identifying the kind of problem
+    #[serde(rename = "type")]
+    pub r#type: String,
+    /// Short summary of the kind of problem
+    pub title: String,
+    /// HTTP status code of the response
+    pub status: u16,
+    /// Explanation specific to this occurrence of the problem
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Path of the request that caused the problem
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Id of the request that caused the problem, as in the `X-Request-Id` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub code: ProblemCode,
+    /// Errors on individual fields of the input, if any
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, code: ProblemCode, detail: impl Into<String>) -> Self {
+        let code_name = serde_json::to_value(code)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_owned))
+            .unwrap_or_default();
+
+        Problem {
+            r#type: format!("urn:rust-movies:problem:{code_name}"),
+            title: code.title().to_owned(),
+            status: status.as_u16(),
+            detail: Some(detail.into()),
+            instance: None,
+            request_id: None,
+            code,
+            errors: vec![],
+        }
+    }
+
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::BAD_REQUEST, ProblemCode::BadRequest, detail)
+    }
+
+    pub fn unauthorized(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::UNAUTHORIZED, ProblemCode::Unauthorized, detail)
+    }
+
+    pub fn forbidden(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::FORBIDDEN, ProblemCode::Forbidden, detail)
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Problem::new(StatusCode::NOT_FOUND, ProblemCode::NotFound, detail)
+    }
+
+    pub fn validation(errors: Vec<FieldError>) -> Self {
+        Problem {
+            errors,
+            ..Problem::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ProblemCode::ValidationFailed,
+                "Some fields of the input are invalid",
+            )
+        }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_vec(&self).unwrap_or_default();
+
+        let mut response = (
+            status,
+            [(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON))],
+            body,
+        )
+            .into_response();
+
+        // Kept around for middlewares adding request specific details, see `problem_context`
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+/// Fill in the `instance` and `request_id` of problem responses from the request.
+pub async fn problem_context(request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_owned();
+    let request_id = request.extensions().get::<RequestId>().cloned();
+
+    let mut response = next.run(request).await;
+
+    match response.extensions_mut().remove::<Problem>() {
+        Some(mut problem) => {
+            problem.instance.get_or_insert(path);
+            if let Some(RequestId(request_id)) = request_id {
+                problem.request_id.get_or_insert(request_id);
+            }
+
+            let (mut parts, _) = response.into_parts();
+            let body = Body::from(serde_json::to_vec(&problem).unwrap_or_default());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.extensions.insert(problem);
+
+            Response::from_parts(parts, body)
+        }
+        None => response,
+    }
+}
+
+/// Errors of routes needing an API key, when it is missing or invalid, or lacks a scope.
+#[derive(IntoResponse, IntoResponses)]
+pub enum AuthErrorResponses {
+    #[response(status = UNAUTHORIZED, content_type = "application/problem+json")]
+    Unauthorized(Problem),
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+}
+
+/// Errors the database can fail with, whatever the operation.
+///
+/// The details are the same for every occurrence of a kind of error, the messages of the database
+/// naming tables and constraints are only logged.
+#[derive(IntoResponse, IntoResponses)]
+pub enum DatabaseErrorResponses {
+    #[response(status = CONFLICT, content_type = "application/problem+json")]
+    Conflict(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+
+    #[response(status = INTERNAL_SERVER_ERROR, content_type = "application/problem+json")]
+    DatabaseError(Problem),
+
+    #[response(status = SERVICE_UNAVAILABLE, content_type = "application/problem+json")]
+    ServiceUnavailable(Problem),
+}
+
+impl From<DbErr> for DatabaseErrorResponses {
+    fn from(err: DbErr) -> Self {
+        match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(message)) => {
+                warn!("Unique constraint violation: {message}");
+
+                return DatabaseErrorResponses::Conflict(Problem::new(
+                    StatusCode::CONFLICT,
+                    ProblemCode::UniqueViolation,
+                    "A resource with the same unique values already exists",
+                ));
+            }
+            Some(SqlErr::ForeignKeyConstraintViolation(message)) => {
+                warn!("Foreign key constraint violation: {message}");
+
+                return DatabaseErrorResponses::UnprocessableEntity(Problem::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ProblemCode::ForeignKeyViolation,
+                    "A resource referred to does not exist, or is still referred to",
+                ));
+            }
+            _ => {}
+        }
+
+        match &err {
+            DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(db_err)))
+            | DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(db_err)))
+                if is_check_violation(db_err.code().as_deref()) =>
+            {
+                warn!("Check constraint violation: {}", db_err.message());
+
+                DatabaseErrorResponses::UnprocessableEntity(Problem::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ProblemCode::CheckViolation,
+                    "Some values of the input are out of their allowed range",
+                ))
+            }
+            DbErr::ConnectionAcquire(_)
+            | DbErr::Conn(_)
+            | DbErr::Exec(RuntimeErr::SqlxError(
+                sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed,
+            ))
+            | DbErr::Query(RuntimeErr::SqlxError(
+                sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed,
+            )) => {
+                error!("Database unavailable: {err}");
+
+                DatabaseErrorResponses::ServiceUnavailable(Problem::new(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    ProblemCode::DatabaseUnavailable,
+                    "The database is unavailable, try again later",
+                ))
+            }
+            _ => {
+                error!("Database error: {err}");
+
+                DatabaseErrorResponses::DatabaseError(Problem::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ProblemCode::DatabaseError,
+                    "Database error",
+                ))
+            }
+        }
+    }
+}
+
+/// Whether an SQLSTATE code is a violation of a CHECK or NOT NULL constraint.
+fn is_check_violation(code: Option<&str>) -> bool {
+    matches!(code, Some("23514" | "23502"))
+}
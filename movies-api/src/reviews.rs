@@ -0,0 +1,443 @@
+use movies_core::sea_orm::{ActiveEnum, DatabaseConnection, DeleteResult};
+use movies_core::{CreateReview, FieldError, ModerateReview, Pagination, PartialReview, Validate};
+use movies_entity::review::Model as Review;
+use movies_entity::sea_orm_active_enums::ReviewStatus;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{get, put};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, Caller, CurrentUser, Moderate, Operation, View};
+use crate::extract::{Json, Path};
+use crate::pagination::{Page, PaginationParams, ReviewsPage};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_reviews,
+        create_review,
+        get_review_queue,
+        get_review,
+        update_review,
+        delete_review,
+        moderate_review,
+    ),
+    components(schemas(
+        Review,
+        ReviewStatus,
+        ReviewsPage,
+        CreateReview,
+        PartialReview,
+        ModerateReview,
+        Problem,
+        ProblemCode,
+        FieldError
+    )),
+    tags((
+        name = "reviews",
+        description = "Reviews users write of movies in markdown, published once a moderator \
+                       approves them"
+    ))
+)]
+pub struct ReviewsApiDocs;
+
+pub fn reviews_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/", get(list_reviews).post(create_review))
+        .route("/queue", get(get_review_queue))
+        .route(
+            "/:id",
+            get(get_review).patch(update_review).delete(delete_review),
+        )
+        .route("/:id/status", put(moderate_review))
+        .with_state(ReviewsState { db })
+}
+
+#[derive(Clone)]
+struct ReviewsState {
+    db: DatabaseConnection,
+}
+
+impl FromRef<ReviewsState> for DatabaseConnection {
+    fn from_ref(state: &ReviewsState) -> Self {
+        state.db.clone()
+    }
+}
+
+fn is_author(caller: &Caller, review: &Review) -> bool {
+    matches!(caller, Caller::User(user) if user.id == review.author_id)
+}
+
+/// Whether the caller may see a review, which for reviews that are not published yet or hidden
+/// means being their author or a moderator.
+fn may_see(caller: &Caller, review: &Review) -> bool {
+    review.status == ReviewStatus::Published
+        || caller.role().grants(Moderate::ROLE)
+        || is_author(caller, review)
+}
+
+fn review_not_found(id: i32) -> Problem {
+    Problem::not_found(format!("Review with id `{id}` not found"))
+}
+
+fn parse_pagination(
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+) -> Result<Pagination, String> {
+    match pagination {
+        Ok(Query(pagination)) => (&pagination).try_into(),
+        Err(rejection) => Err(rejection.body_text()),
+    }
+}
+
+/// Filtering of the review list
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ReviewListParams {
+    /// Only reviews of this movie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    movie_id: Option<i32>,
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListReviewsResponses {
+    #[response(status = OK)]
+    Success(#[json] ReviewsPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Get a page of the published reviews
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/reviews",
+    params(PaginationParams, ReviewListParams),
+    responses(ListReviewsResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "reviews"
+)]
+async fn list_reviews(
+    state: State<ReviewsState>,
+    _caller: Authorized<View>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+    params: Result<Query<ReviewListParams>, QueryRejection>,
+) -> Result<ListReviewsResponses, DatabaseErrorResponses> {
+    let pagination = match parse_pagination(pagination) {
+        Ok(pagination) => pagination,
+        Err(message) => {
+            return Ok(ListReviewsResponses::BadRequest(Problem::bad_request(
+                message,
+            )))
+        }
+    };
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return Ok(ListReviewsResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    match movies_core::get_published_reviews(&state.db, params.movie_id, pagination).await {
+        Ok(page) => Ok(ListReviewsResponses::Success(Page::new(
+            page, "/reviews", &params,
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum CreateReviewResponses {
+    #[response(status = OK)]
+    Success(#[json] Review),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Write a review of an existing movie as the logged in user
+///
+/// The review is only published once a moderator approves it.
+#[utoipa::path(
+        post,
+        path = "/reviews",
+        request_body = CreateReview,
+        responses(CreateReviewResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "reviews"
+    )]
+async fn create_review(
+    state: State<ReviewsState>,
+    CurrentUser(user): CurrentUser,
+    Json(data): Json<CreateReview>,
+) -> Result<CreateReviewResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(CreateReviewResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    match movies_core::create_review(&state.db, user.id, data).await {
+        Ok(review) => Ok(CreateReviewResponses::Success(review)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(CreateReviewResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetReviewQueueResponses {
+    #[response(status = OK)]
+    Success(#[json] ReviewsPage),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Get a page of the reviews waiting for a moderator, oldest first
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+    get,
+    path = "/reviews/queue",
+    params(PaginationParams),
+    responses(GetReviewQueueResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security(("api_key" = []), ("session" = [])),
+    tag = "reviews"
+)]
+async fn get_review_queue(
+    state: State<ReviewsState>,
+    _caller: Authorized<Moderate>,
+    pagination: Result<Query<PaginationParams>, QueryRejection>,
+) -> Result<GetReviewQueueResponses, DatabaseErrorResponses> {
+    let pagination = match parse_pagination(pagination) {
+        Ok(pagination) => pagination,
+        Err(message) => {
+            return Ok(GetReviewQueueResponses::BadRequest(Problem::bad_request(
+                message,
+            )))
+        }
+    };
+
+    match movies_core::get_pending_reviews(&state.db, pagination).await {
+        Ok(page) => Ok(GetReviewQueueResponses::Success(Page::new(
+            page,
+            "/reviews/queue",
+            &(),
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum GetReviewResponses {
+    #[response(status = OK)]
+    Success(#[json] Review),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Get an existing review by id
+///
+/// Needs the `viewer` role, which anonymous callers have. Reviews that are not published are only
+/// found by their author and moderators.
+#[utoipa::path(
+        get,
+        path = "/reviews/{id}",
+        params(
+            ("id", description = "Review id")
+        ),
+        responses(GetReviewResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security((), ("api_key" = []), ("session" = [])),
+        tag = "reviews"
+    )]
+async fn get_review(
+    state: State<ReviewsState>,
+    Authorized { caller, .. }: Authorized<View>,
+    Path(id): Path<i32>,
+) -> Result<GetReviewResponses, DatabaseErrorResponses> {
+    match movies_core::get_review(&state.db, id).await {
+        Ok(Some(review)) if may_see(&caller, &review) => Ok(GetReviewResponses::Success(review)),
+        Ok(_) => Ok(GetReviewResponses::NotFound(review_not_found(id))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdateReviewResponses {
+    #[response(status = OK)]
+    Success(#[json] Review),
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = UNPROCESSABLE_ENTITY, content_type = "application/problem+json")]
+    UnprocessableEntity(Problem),
+}
+
+/// Partially update an existing review by id, written by the logged in user
+///
+/// The review goes back to moderation, and is no longer published until a moderator approves it
+/// again.
+#[utoipa::path(
+        patch,
+        path = "/reviews/{id}",
+        params(
+            ("id", description = "Review id")
+        ),
+        request_body = PartialReview,
+        responses(UpdateReviewResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "reviews"
+    )]
+async fn update_review(
+    state: State<ReviewsState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<i32>,
+    Json(data): Json<PartialReview>,
+) -> Result<UpdateReviewResponses, DatabaseErrorResponses> {
+    if let Err(errors) = data.validate() {
+        return Ok(UpdateReviewResponses::UnprocessableEntity(
+            Problem::validation(errors),
+        ));
+    }
+
+    let caller = Caller::User(user);
+    match movies_core::get_review(&state.db, id).await? {
+        Some(review) if is_author(&caller, &review) => {}
+        Some(review) if may_see(&caller, &review) => {
+            return Ok(UpdateReviewResponses::Forbidden(Problem::forbidden(
+                "Only the author of a review may edit it",
+            )))
+        }
+        _ => return Ok(UpdateReviewResponses::NotFound(review_not_found(id))),
+    }
+
+    match movies_core::update_review(&state.db, id, data).await {
+        Ok(review) => Ok(UpdateReviewResponses::Success(review)),
+        Err(DbErr::RecordNotFound(message)) => {
+            Ok(UpdateReviewResponses::NotFound(Problem::not_found(message)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeleteReviewResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = FORBIDDEN, content_type = "application/problem+json")]
+    Forbidden(Problem),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing review by id, written by the logged in user
+///
+/// Users with the `admin` role may delete the reviews of others as well.
+#[utoipa::path(
+        delete,
+        path = "/reviews/{id}",
+        params(
+            ("id", description = "Review id")
+        ),
+        responses(DeleteReviewResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("session" = [])),
+        tag = "reviews"
+    )]
+async fn delete_review(
+    state: State<ReviewsState>,
+    CurrentUser(user): CurrentUser,
+    Path(id): Path<i32>,
+) -> Result<DeleteReviewResponses, DatabaseErrorResponses> {
+    let caller = Caller::User(user);
+    match movies_core::get_review(&state.db, id).await? {
+        Some(review) if may_see(&caller, &review) => {
+            if !is_author(&caller, &review) && !caller.role().grants(Moderate::ROLE) {
+                return Ok(DeleteReviewResponses::Forbidden(Problem::forbidden(
+                    "Only the author of a review or a moderator may delete it",
+                )));
+            }
+        }
+        _ => return Ok(DeleteReviewResponses::NotFound(review_not_found(id))),
+    }
+
+    match movies_core::delete_review(&state.db, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => {
+            Ok(DeleteReviewResponses::Success)
+        }
+        Ok(_) => Ok(DeleteReviewResponses::NotFound(review_not_found(id))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ModerateReviewResponses {
+    #[response(status = OK)]
+    Success(#[json] Review),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+
+    #[response(status = CONFLICT, content_type = "application/problem+json")]
+    Conflict(Problem),
+}
+
+/// Publish or hide an existing review by id
+///
+/// Needs the `admin` role. Pending reviews can be published or hidden, published ones hidden and
+/// hidden ones published again.
+#[utoipa::path(
+        put,
+        path = "/reviews/{id}/status",
+        params(
+            ("id", description = "Review id")
+        ),
+        request_body = ModerateReview,
+        responses(ModerateReviewResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "reviews"
+    )]
+async fn moderate_review(
+    state: State<ReviewsState>,
+    _caller: Authorized<Moderate>,
+    Path(id): Path<i32>,
+    Json(data): Json<ModerateReview>,
+) -> Result<ModerateReviewResponses, DatabaseErrorResponses> {
+    match movies_core::moderate_review(&state.db, id, data.status).await {
+        Ok(Some(review)) => Ok(ModerateReviewResponses::Success(review)),
+        Ok(None) => Ok(ModerateReviewResponses::Conflict(Problem::new(
+            StatusCode::CONFLICT,
+            ProblemCode::InvalidTransition,
+            format!(
+                "The review with id `{id}` cannot become `{}` from its current status",
+                data.status.to_value()
+            ),
+        ))),
+        Err(DbErr::RecordNotFound(message)) => Ok(ModerateReviewResponses::NotFound(
+            Problem::not_found(message),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
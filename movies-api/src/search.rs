@@ -0,0 +1,189 @@
+use movies_core::sea_orm::DatabaseConnection;
+use movies_core::{SearchHit, SearchHitType, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+use movies_macros::IntoResponse;
+use movies_search::{IndexHit, SearchIndex};
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{FromRef, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+use utoipa::{IntoParams, IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, View};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(search, fuzzy_search),
+    components(schemas(SearchHit, SearchHitType, IndexHit, Problem, ProblemCode)),
+    tags((
+        name = "search",
+        description = "Search of movie titles and descriptions and person names"
+    ))
+)]
+pub struct SearchApiDocs;
+
+pub fn search_routes(db: DatabaseConnection, index: Arc<SearchIndex>) -> Router {
+    Router::new()
+        .route("/", get(search))
+        .route("/fuzzy", get(fuzzy_search))
+        .with_state(SearchState { db, index })
+}
+
+#[derive(Clone)]
+struct SearchState {
+    db: DatabaseConnection,
+    index: Arc<SearchIndex>,
+}
+
+impl FromRef<SearchState> for DatabaseConnection {
+    fn from_ref(state: &SearchState) -> Self {
+        state.db.clone()
+    }
+}
+
+/// Query parameters of a search
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SearchParams {
+    /// Words to search, with `"quoted phrases"`, `or` and `-excluded` words
+    q: String,
+
+    /// Maximum number of hits
+    #[param(minimum = 1, maximum = 100, default = 20)]
+    limit: Option<u64>,
+}
+
+impl SearchParams {
+    /// Check the parameters, returning the limit.
+    fn validate(&self) -> Result<u64, String> {
+        if self.q.trim().is_empty() {
+            return Err("`q` must not be blank".to_owned());
+        }
+
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+            return Err(format!("`limit` must be between 1 and {MAX_PAGE_SIZE}"));
+        }
+
+        Ok(limit)
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum SearchResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<SearchHit>),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Search movies and persons, best hits first
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SearchParams),
+    responses(SearchResponses, AuthErrorResponses, DatabaseErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "search"
+)]
+async fn search(
+    state: State<SearchState>,
+    _caller: Authorized<View>,
+    params: Result<Query<SearchParams>, QueryRejection>,
+) -> Result<SearchResponses, DatabaseErrorResponses> {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return Ok(SearchResponses::BadRequest(Problem::bad_request(
+                rejection.body_text(),
+            )))
+        }
+    };
+
+    let limit = match params.validate() {
+        Ok(limit) => limit,
+        Err(message) => return Ok(SearchResponses::BadRequest(Problem::bad_request(message))),
+    };
+
+    match movies_core::search(&state.db, &params.q, limit).await {
+        Ok(hits) => Ok(SearchResponses::Success(hits)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum FuzzySearchResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<IndexHit>),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+
+    #[response(status = INTERNAL_SERVER_ERROR, content_type = "application/problem+json")]
+    InternalServerError(Problem),
+}
+
+/// Search movies and persons tolerating typos, best hits first
+///
+/// Every word must match, up to two typos away, and the last one also matches the start of
+/// longer words so that it can be used while typing. Quotes and operators are not supported.
+///
+/// Needs the `viewer` role, which anonymous callers have.
+#[utoipa::path(
+    get,
+    path = "/search/fuzzy",
+    params(SearchParams),
+    responses(FuzzySearchResponses, AuthErrorResponses),
+    security((), ("api_key" = []), ("session" = [])),
+    tag = "search"
+)]
+async fn fuzzy_search(
+    state: State<SearchState>,
+    _caller: Authorized<View>,
+    params: Result<Query<SearchParams>, QueryRejection>,
+) -> FuzzySearchResponses {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return FuzzySearchResponses::BadRequest(Problem::bad_request(rejection.body_text()))
+        }
+    };
+
+    let limit = match params.validate() {
+        Ok(limit) => limit,
+        Err(message) => return FuzzySearchResponses::BadRequest(Problem::bad_request(message)),
+    };
+
+    // Searching reads index segments from disk, off the async runtime
+    let index = state.index.clone();
+    let hits = tokio::task::spawn_blocking(move || index.search(&params.q, limit as usize)).await;
+
+    match hits {
+        Ok(Ok(hits)) => FuzzySearchResponses::Success(hits),
+        Ok(Err(err)) => {
+            error!("Cannot search the index: {err}");
+            FuzzySearchResponses::InternalServerError(search_index_error())
+        }
+        Err(err) => {
+            error!("Searching the index failed: {err}");
+            FuzzySearchResponses::InternalServerError(search_index_error())
+        }
+    }
+}
+
+fn search_index_error() -> Problem {
+    Problem::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        ProblemCode::SearchIndexError,
+        "Search index error",
+    )
+}
@@ -0,0 +1,120 @@
+use movies_core::SearchHitType;
+use movies_macros::IntoResponse;
+use movies_search::{Suggestion, Suggestions, MAX_SUGGESTIONS};
+
+use axum::extract::rejection::QueryRejection;
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::{IntoParams, IntoResponses, OpenApi};
+
+use crate::responses::{Problem, ProblemCode};
+
+/// Number of suggestions returned when no `limit` is given.
+const DEFAULT_SUGGESTIONS: usize = 10;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(suggest),
+    components(schemas(Suggestion, SearchHitType, Problem, ProblemCode))
+)]
+pub struct SuggestApiDocs;
+
+pub fn suggest_routes(suggestions: Arc<Suggestions>) -> Router {
+    Router::new()
+        .route("/", get(suggest))
+        .with_state(SuggestState { suggestions })
+}
+
+#[derive(Clone)]
+struct SuggestState {
+    suggestions: Arc<Suggestions>,
+}
+
+/// Query parameters of suggestions
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SuggestParams {
+    /// Start of the title or name, or of any of their words
+    q: String,
+
+    /// What to suggest, as a comma separated list of `movie` and `person`
+    #[param(default = "movie,person")]
+    types: Option<String>,
+
+    /// Maximum number of suggestions
+    #[param(minimum = 1, maximum = 20, default = 10)]
+    limit: Option<usize>,
+}
+
+impl SuggestParams {
+    /// Check the parameters, returning the types and the limit.
+    fn validate(&self) -> Result<(Vec<SearchHitType>, usize), String> {
+        if self.q.trim().is_empty() {
+            return Err("`q` must not be blank".to_owned());
+        }
+
+        let types = match &self.types {
+            None => vec![SearchHitType::Movie, SearchHitType::Person],
+            Some(types) => types
+                .split(',')
+                .map(|r#type| match r#type.trim() {
+                    "movie" => Ok(SearchHitType::Movie),
+                    "person" => Ok(SearchHitType::Person),
+                    other => Err(format!(
+                        "unknown type `{other}` in `types`, expected `movie` or `person`"
+                    )),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        let limit = self.limit.unwrap_or(DEFAULT_SUGGESTIONS);
+        if !(1..=MAX_SUGGESTIONS).contains(&limit) {
+            return Err(format!("`limit` must be between 1 and {MAX_SUGGESTIONS}"));
+        }
+
+        Ok((types, limit))
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum SuggestResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<Suggestion>),
+
+    #[response(status = BAD_REQUEST, content_type = "application/problem+json")]
+    BadRequest(Problem),
+}
+
+/// Suggest movies and persons whose title or name starts with what is typed, best first
+///
+/// Labels matching from their first word come first, then shorter ones. Served from memory
+/// without looking up credentials, for search as you type, as anyone may read movies and persons.
+#[utoipa::path(
+    get,
+    path = "/suggest",
+    params(SuggestParams),
+    responses(SuggestResponses),
+    tag = "search"
+)]
+async fn suggest(
+    state: State<SuggestState>,
+    params: Result<Query<SuggestParams>, QueryRejection>,
+) -> SuggestResponses {
+    let params = match params {
+        Ok(Query(params)) => params,
+        Err(rejection) => {
+            return SuggestResponses::BadRequest(Problem::bad_request(rejection.body_text()))
+        }
+    };
+
+    match params.validate() {
+        Ok((types, limit)) => {
+            SuggestResponses::Success(state.suggestions.suggest(&params.q, &types, limit))
+        }
+        Err(message) => SuggestResponses::BadRequest(Problem::bad_request(message)),
+    }
+}
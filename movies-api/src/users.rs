@@ -0,0 +1,144 @@
+use movies_core::sea_orm::{DatabaseConnection, DeleteResult};
+use movies_core::{UpdateUserRole, UserResponse};
+use movies_entity::sea_orm_active_enums::UserRole;
+use movies_macros::IntoResponse;
+use movies_migration::DbErr;
+
+use axum::extract::{FromRef, State};
+use axum::response::IntoResponse;
+use axum::routing::{get, patch};
+use axum::Router;
+use utoipa::{IntoResponses, OpenApi};
+
+use crate::auth::{Authorized, ManageUsers};
+use crate::extract::{Json, Path};
+use crate::responses::{AuthErrorResponses, DatabaseErrorResponses, Problem, ProblemCode};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_users, update_user_role, delete_user),
+    components(schemas(UserResponse, UpdateUserRole, UserRole, Problem, ProblemCode)),
+    tags((name = "users", description = "Management of user accounts by admins"))
+)]
+pub struct UsersApiDocs;
+
+pub fn users_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/", get(list_users))
+        .route("/:id", patch(update_user_role).delete(delete_user))
+        .with_state(UsersState { db })
+}
+
+#[derive(Clone)]
+struct UsersState {
+    db: DatabaseConnection,
+}
+
+impl FromRef<UsersState> for DatabaseConnection {
+    fn from_ref(state: &UsersState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum ListUsersResponses {
+    #[response(status = OK)]
+    Success(#[json] Vec<UserResponse>),
+}
+
+/// Get every user
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+        get,
+        path = "/users",
+        responses(ListUsersResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "users"
+    )]
+async fn list_users(
+    state: State<UsersState>,
+    _caller: Authorized<ManageUsers>,
+) -> Result<ListUsersResponses, DatabaseErrorResponses> {
+    match movies_core::get_all_users(&state.db).await {
+        Ok(users) => Ok(ListUsersResponses::Success(
+            users.into_iter().map(Into::into).collect(),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum UpdateUserRoleResponses {
+    #[response(status = OK)]
+    Success(#[json] UserResponse),
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Change the role of an existing user by id
+///
+/// Needs the `admin` role. Users logging in with an OpenID Connect provider configured with a
+/// role claim get their role from the provider again on their next login.
+#[utoipa::path(
+        patch,
+        path = "/users/{id}",
+        params(
+            ("id", description = "User id")
+        ),
+        request_body = UpdateUserRole,
+        responses(UpdateUserRoleResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "users"
+    )]
+async fn update_user_role(
+    state: State<UsersState>,
+    _caller: Authorized<ManageUsers>,
+    Path(id): Path<i32>,
+    Json(data): Json<UpdateUserRole>,
+) -> Result<UpdateUserRoleResponses, DatabaseErrorResponses> {
+    match movies_core::update_user_role(&state.db, id, data.role).await {
+        Ok(user) => Ok(UpdateUserRoleResponses::Success(user.into())),
+        Err(DbErr::RecordNotFound(message)) => Ok(UpdateUserRoleResponses::NotFound(
+            Problem::not_found(message),
+        )),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[derive(IntoResponse, IntoResponses)]
+enum DeleteUserResponses {
+    #[response(status = NO_CONTENT)]
+    Success,
+
+    #[response(status = NOT_FOUND, content_type = "application/problem+json")]
+    NotFound(Problem),
+}
+
+/// Delete an existing user by id, logging them out
+///
+/// Needs the `admin` role.
+#[utoipa::path(
+        delete,
+        path = "/users/{id}",
+        params(
+            ("id", description = "User id")
+        ),
+        responses(DeleteUserResponses, AuthErrorResponses, DatabaseErrorResponses),
+        security(("api_key" = []), ("session" = [])),
+        tag = "users"
+    )]
+async fn delete_user(
+    state: State<UsersState>,
+    _caller: Authorized<ManageUsers>,
+    Path(id): Path<i32>,
+) -> Result<DeleteUserResponses, DatabaseErrorResponses> {
+    match movies_core::delete_user(&state.db, id).await {
+        Ok(DeleteResult { rows_affected }) if rows_affected > 0 => Ok(DeleteUserResponses::Success),
+        Ok(_) => Ok(DeleteUserResponses::NotFound(Problem::not_found(format!(
+            "User with id `{id}` not found"
+        )))),
+        Err(err) => Err(err.into()),
+    }
+}
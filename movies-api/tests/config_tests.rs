@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::time::Duration;
+
+use movies_api::config::{ConfigLayer, LogFormat, MigrationMode};
+use movies_core::RatingScale;
+
+fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn later_layers_override_earlier_ones() {
+    let file = ConfigLayer::from_toml(
+        r#"
+        migrations = "check"
+
+        [server]
+        host = "0.0.0.0"
+        port = 8000
+        shutdown_timeout_secs = 5
+
+        [database]
+        url = "postgres://file/movies"
+        max_connections = 5
+
+        [log]
+        format = "json"
+
+        [search]
+        index_dir = "/var/lib/movies/file-index"
+
+        [ratings]
+        min = 1
+        max = 10
+        "#,
+    )
+    .unwrap();
+    let env = ConfigLayer::from_env_vars(env_vars(&[
+        ("PORT", "9000"),
+        ("DATABASE_URL", "postgres://env/movies"),
+        ("HOST", ""),
+        ("SEARCH_INDEX_DIR", "/var/lib/movies/env-index"),
+        ("RATING_MAX", "5"),
+    ]))
+    .unwrap();
+    let mut flags = ConfigLayer::default();
+    flags.database.url = Some("postgres://flags/movies".into());
+
+    let config = file.merge(env).merge(flags).build().unwrap();
+
+    assert_eq!(config.server.host, "0.0.0.0");
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.server.shutdown_timeout, Duration::from_secs(5));
+    assert_eq!(config.database.url, "postgres://flags/movies");
+    assert_eq!(config.database.max_connections, 5);
+    assert_eq!(config.database.acquire_timeout, Duration::from_secs(30));
+    assert_eq!(config.log.format, LogFormat::Json);
+    assert_eq!(
+        config.search.index_dir.as_deref(),
+        Some(Path::new("/var/lib/movies/env-index"))
+    );
+    assert_eq!(config.ratings, RatingScale::new(1, 5).unwrap());
+    assert_eq!(config.migrations, MigrationMode::Check);
+}
+
+#[test]
+fn report_every_invalid_setting() {
+    let env = ConfigLayer::from_env_vars(env_vars(&[
+        ("PORT", "http"),
+        ("LOG_FORMAT", "xml"),
+        ("MIGRATIONS", "yes"),
+        ("SESSION_SECURE_COOKIE", "maybe"),
+    ]));
+    let file = ConfigLayer::from_toml(
+        r#"
+        [database]
+        max_connections = 2
+        min_connections = 3
+        idle_timeout_secs = 0
+
+        [otel]
+        sampling_ratio = 1.5
+
+        [session]
+        ttl_secs = 0
+
+        [ratings]
+        max = 11
+        "#,
+    )
+    .unwrap();
+
+    let env_errors = env.unwrap_err().errors;
+    let build_errors = file.build().unwrap_err().errors;
+
+    assert_eq!(env_errors.len(), 4);
+    assert!(env_errors[0].starts_with("PORT"));
+    assert!(env_errors[1].starts_with("LOG_FORMAT"));
+    assert!(env_errors[2].starts_with("MIGRATIONS"));
+    assert!(env_errors[3].starts_with("SESSION_SECURE_COOKIE"));
+    assert_eq!(build_errors.len(), 6);
+    assert!(build_errors[0].starts_with("database.url"));
+    assert!(build_errors[1].starts_with("database.min_connections"));
+    assert!(build_errors[2].starts_with("database.idle_timeout_secs"));
+    assert!(build_errors[3].starts_with("otel.sampling_ratio"));
+    assert!(build_errors[4].starts_with("session.ttl_secs"));
+    assert!(build_errors[5].starts_with("ratings.min and ratings.max"));
+}
+
+#[test]
+fn reject_unknown_settings_in_config_file() {
+    let result = ConfigLayer::from_toml("[server]\nadress = \"localhost\"\n");
+
+    assert!(result.unwrap_err().errors[0].contains("adress"));
+}
@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use ::movies_entity::{movie, person};
+
+/// A movie or person saved or deleted by the mutation functions of this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    MovieSaved(movie::Model),
+    MovieDeleted(i32),
+    PersonSaved(person::Model),
+    PersonDeleted(i32),
+}
+
+/// Told about every [`Change`] once it is committed, to keep copies of the data up to date.
+///
+/// The mutation functions writing movies and persons take one, and call it from async code once
+/// their changes are committed, so it must not block.
+pub trait ChangeListener: Send + Sync {
+    fn changed(&self, change: Change);
+}
+
+/// Tell every listener of the list, in order.
+impl ChangeListener for Vec<Arc<dyn ChangeListener>> {
+    fn changed(&self, change: Change) {
+        for listener in self {
+            listener.changed(change.clone());
+        }
+    }
+}
+
+/// Ignore every change, when no copy of the data is kept.
+impl ChangeListener for () {
+    fn changed(&self, _change: Change) {}
+}
@@ -0,0 +1,379 @@
+use ::movies_entity::movie::RatingHistogram;
+use ::movies_entity::sea_orm_active_enums::{ListVisibility, ReviewStatus, UserRole};
+use ::movies_entity::{genre, keyword, list, movie, user};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::validation::MAX_RATING;
+
+/// Fields of a new movie. The id is assigned by the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CreateMovie {
+    pub title: String,
+    pub release_date: chrono::DateTime<chrono::Utc>,
+    pub poster_url: String,
+    pub description: String,
+    pub rating: i32,
+}
+
+impl CreateMovie {
+    pub fn into_active_model(self) -> movie::ActiveModel {
+        movie::ActiveModel {
+            id: NotSet,
+            title: Set(self.title.trim().to_owned()),
+            release_date: Set(self.release_date),
+            poster_url: Set(self.poster_url),
+            description: Set(self.description),
+            rating: Set(self.rating),
+            user_rating_histogram: Set(RatingHistogram::default()),
+        }
+    }
+}
+
+/// Every field of an existing movie, replacing the current ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReplaceMovie {
+    pub title: String,
+    pub release_date: chrono::DateTime<chrono::Utc>,
+    pub poster_url: String,
+    pub description: String,
+    pub rating: i32,
+}
+
+impl ReplaceMovie {
+    pub fn into_active_model(self, id: i32) -> movie::ActiveModel {
+        movie::ActiveModel {
+            id: Unchanged(id),
+            title: Set(self.title.trim().to_owned()),
+            release_date: Set(self.release_date),
+            poster_url: Set(self.poster_url),
+            description: Set(self.description),
+            rating: Set(self.rating),
+            user_rating_histogram: NotSet,
+        }
+    }
+}
+
+/// Some fields of an existing movie, replacing the current ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PartialMovie {
+    pub title: Option<String>,
+    pub release_date: Option<chrono::DateTime<chrono::Utc>>,
+    pub poster_url: Option<String>,
+    pub description: Option<String>,
+    pub rating: Option<i32>,
+}
+
+impl PartialMovie {
+    pub fn into_active_model(self, id: i32) -> movie::ActiveModel {
+        movie::ActiveModel {
+            id: Unchanged(id),
+            title: option_into_active_value(self.title.map(|title| title.trim().to_owned())),
+            release_date: option_into_active_value(self.release_date),
+            poster_url: option_into_active_value(self.poster_url),
+            description: option_into_active_value(self.description),
+            rating: option_into_active_value(self.rating),
+            user_rating_histogram: NotSet,
+        }
+    }
+}
+
+pub(crate) fn option_into_active_value<T>(maybe_value: Option<T>) -> ActiveValue<T>
+where
+    T: Into<Value>,
+{
+    match maybe_value {
+        Some(value) => Set(value),
+        None => NotSet,
+    }
+}
+
+/// A movie as returned by the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MovieResponse {
+    pub id: i32,
+    pub title: String,
+    pub release_date: chrono::DateTime<chrono::Utc>,
+    pub poster_url: String,
+    pub description: String,
+    /// Rating given by the editors of the movie, on the configured scale, from 0 to 10 unless
+    /// narrowed. It is not derived from `user_ratings`, which users give on the same scale
+    pub rating: i32,
+    pub user_ratings: RatingSummary,
+}
+
+/// The ratings users gave a movie.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RatingSummary {
+    /// Average score, absent until the movie is rated
+    pub average: Option<f64>,
+    pub count: i32,
+    /// Number of ratings with each score from 0 to 10, whatever the configured scale
+    pub histogram: Vec<i32>,
+}
+
+impl From<&RatingHistogram> for RatingSummary {
+    fn from(histogram: &RatingHistogram) -> Self {
+        let mut counts = histogram.0.clone();
+        counts.resize(MAX_RATING as usize + 1, 0);
+
+        let count: i32 = counts.iter().sum();
+        let total: i64 = counts
+            .iter()
+            .zip(0..)
+            .map(|(&ratings, score)| i64::from(ratings) * score)
+            .sum();
+
+        RatingSummary {
+            average: (count > 0).then(|| total as f64 / f64::from(count)),
+            count,
+            histogram: counts,
+        }
+    }
+}
+
+/// Score given to a movie by the logged in user.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct RateMovie {
+    /// On the configured scale, from 0 to 10 unless narrowed
+    pub score: i32,
+}
+
+impl From<movie::Model> for MovieResponse {
+    fn from(movie: movie::Model) -> Self {
+        MovieResponse {
+            id: movie.id,
+            title: movie.title,
+            release_date: movie.release_date,
+            poster_url: movie.poster_url,
+            description: movie.description,
+            rating: movie.rating,
+            user_ratings: (&movie.user_rating_histogram).into(),
+        }
+    }
+}
+
+/// The fields of a movie worth showing wherever it is mentioned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MovieSummary {
+    pub id: i32,
+    pub title: String,
+    pub release_date: chrono::DateTime<chrono::Utc>,
+    pub poster_url: String,
+}
+
+impl From<movie::Model> for MovieSummary {
+    fn from(movie: movie::Model) -> Self {
+        MovieSummary {
+            id: movie.id,
+            title: movie.title,
+            release_date: movie.release_date,
+            poster_url: movie.poster_url,
+        }
+    }
+}
+
+/// Fields of a new list of movies, owned by the logged in user.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct CreateList {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Private unless given
+    #[serde(default)]
+    pub visibility: ListVisibility,
+}
+
+/// Some fields of an existing list, replacing the current ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct PartialList {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub visibility: Option<ListVisibility>,
+}
+
+/// A movie to put on a list.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct AddListItem {
+    pub movie_id: i32,
+    #[serde(default)]
+    pub note: String,
+    /// Where to insert the movie, moving the ones from there down; at the end unless given
+    pub position: Option<i32>,
+}
+
+/// New note of a movie on a list.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct UpdateListItem {
+    pub note: String,
+}
+
+/// New order of the items of a list.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct ReorderList {
+    /// Ids of every item of the list, in their new order
+    pub item_ids: Vec<i32>,
+}
+
+/// A review of a movie to write as the logged in user, pending moderation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct CreateReview {
+    pub movie_id: i32,
+    pub title: String,
+    /// Markdown, rendered to HTML in `body_html`
+    pub body: String,
+    /// Whether the review gives away the plot, `false` unless given
+    #[serde(default)]
+    pub spoiler: bool,
+}
+
+/// Some fields of an existing review, replacing the current ones and sending it back to
+/// moderation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct PartialReview {
+    pub title: Option<String>,
+    /// Markdown, rendered to HTML in `body_html`
+    pub body: Option<String>,
+    pub spoiler: Option<bool>,
+}
+
+/// Decision of a moderator on a review.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+pub struct ModerateReview {
+    /// Pending reviews can be published or hidden, published ones hidden and hidden ones
+    /// published again
+    pub status: ReviewStatus,
+}
+
+/// Name of a new genre, or the new name of an existing one. The id is assigned by the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CreateGenre {
+    pub name: String,
+}
+
+impl CreateGenre {
+    pub fn into_active_model(self) -> genre::ActiveModel {
+        genre::ActiveModel {
+            id: NotSet,
+            name: Set(self.name.trim().to_owned()),
+        }
+    }
+}
+
+/// Name of a new keyword, or the new name of an existing one. The id is assigned by the database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CreateKeyword {
+    pub name: String,
+}
+
+impl CreateKeyword {
+    /// Keywords are lowercased, so that each one is only created once whatever its case.
+    pub fn into_active_model(self) -> keyword::ActiveModel {
+        keyword::ActiveModel {
+            id: NotSet,
+            name: Set(self.name.trim().to_lowercase()),
+        }
+    }
+}
+
+/// A movie on a list, as returned by the API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ListItemResponse {
+    pub id: i32,
+    pub position: i32,
+    pub note: String,
+    pub added_at: chrono::DateTime<chrono::Utc>,
+    pub movie: MovieSummary,
+}
+
+/// A list along with its movies, as returned by the API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ListResponse {
+    pub id: i32,
+    pub owner_id: i32,
+    pub name: String,
+    pub description: String,
+    pub visibility: ListVisibility,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub items: Vec<ListItemResponse>,
+}
+
+impl ListResponse {
+    pub fn new(list: list::Model, items: Vec<ListItemResponse>) -> Self {
+        ListResponse {
+            id: list.id,
+            owner_id: list.owner_id,
+            name: list.name,
+            description: list.description,
+            visibility: list.visibility,
+            created_at: list.created_at,
+            updated_at: list.updated_at,
+            items,
+        }
+    }
+}
+
+/// Fields of a new user account. The password is only kept hashed.
+#[derive(Clone, Deserialize, ToSchema)]
+pub struct RegisterUser {
+    pub email: String,
+    pub display_name: String,
+    #[schema(format = Password)]
+    pub password: String,
+}
+
+/// Credentials of an existing user account.
+#[derive(Clone, Deserialize, ToSchema)]
+pub struct LoginUser {
+    pub email: String,
+    #[schema(format = Password)]
+    pub password: String,
+}
+
+/// Emails are compared ignoring case and surrounding whitespace.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// A user account as returned by the API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct UserResponse {
+    pub id: i32,
+    pub email: String,
+    pub display_name: String,
+    pub role: UserRole,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<user::Model> for UserResponse {
+    fn from(user: user::Model) -> Self {
+        UserResponse {
+            id: user.id,
+            email: user.email,
+            display_name: user.display_name,
+            role: user.role,
+            created_at: user.created_at,
+        }
+    }
+}
+
+/// New role of a user.
+#[derive(Clone, Deserialize, ToSchema)]
+pub struct UpdateUserRole {
+    pub role: UserRole,
+}
+
+/// A user as identified by an OpenID Connect provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: String,
+    /// Whether the provider checked that the user owns the email address
+    pub email_verified: bool,
+    pub display_name: String,
+    /// Role given by the claims of the provider, `None` to keep the current one
+    pub role: Option<UserRole>,
+}
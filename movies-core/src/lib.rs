@@ -1,7 +1,23 @@
+mod changes;
+mod dto;
+mod markdown;
 mod mutation;
+mod pagination;
+mod password;
 mod query;
+mod search;
+mod timing;
+mod tokens;
+mod validation;
 
+pub use changes::{Change, ChangeListener};
+pub use dto::*;
 pub use mutation::*;
+pub use pagination::*;
+pub use password::*;
 pub use query::*;
+pub use search::*;
+pub use timing::QUERY_DURATION_METRIC;
+pub use validation::*;
 
 pub use sea_orm;
@@ -0,0 +1,17 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Render markdown written by users to HTML safe to embed in a page.
+///
+/// Scripts, event handlers and other unsafe markup, raw or produced by the markdown, are dropped,
+/// and links are marked so that search engines do not follow them.
+pub(crate) fn render_markdown(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES;
+
+    let mut unsafe_html = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+
+    ammonia::Builder::default()
+        .link_rel(Some("nofollow noopener noreferrer"))
+        .clean(&unsafe_html)
+        .to_string()
+}
@@ -1,86 +1,1149 @@
-use ::movies_entity::movie;
+use ::movies_entity::movie::RatingHistogram;
+use ::movies_entity::sea_orm_active_enums::{ApiKeyScope, ReviewStatus, UserRole};
+use ::movies_entity::{
+    api_key, credit, genre, keyword, list, list_item, movie, movie_genre, movie_keyword, person,
+    review, session, user, user_rating,
+};
+use sea_orm::sea_query::{Expr, OnConflict};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
+use tracing::instrument;
 use utoipa::ToSchema;
 
-pub async fn create_movie(db: &DbConn, data: movie::Model) -> Result<movie::Model, DbErr> {
-    let active_movie = movie::ActiveModel {
-        title: Set(data.title),
-        release_date: Set(data.release_date),
-        poster_url: Set(data.poster_url),
-        description: Set(data.description),
-        rating: Set(data.rating),
+use crate::changes::{Change, ChangeListener};
+use crate::dto::{
+    normalize_email, option_into_active_value, AddListItem, CreateGenre, CreateKeyword, CreateList,
+    CreateMovie, CreateReview, OidcIdentity, PartialList, PartialMovie, PartialReview, ReorderList,
+    ReplaceMovie, UpdateListItem,
+};
+use crate::markdown::render_markdown;
+use crate::timing::QueryTimer;
+use crate::tokens::{generate_api_key, generate_session_token, hash_token};
+
+#[instrument(skip_all)]
+pub async fn create_movie(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    data: CreateMovie,
+) -> Result<movie::Model, DbErr> {
+    let _timer = QueryTimer::start("create_movie");
+
+    let movie = data.into_active_model().insert(db).await?;
+    changes.changed(Change::MovieSaved(movie.clone()));
+
+    Ok(movie)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_movie(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    id: i32,
+) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_movie");
+
+    let result = movie::Entity::delete_by_id(id).exec(db).await?;
+    if result.rows_affected > 0 {
+        changes.changed(Change::MovieDeleted(id));
+    }
+
+    Ok(result)
+}
+
+#[instrument(skip_all)]
+pub async fn update_movie(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    id: i32,
+    data: ReplaceMovie,
+) -> Result<movie::Model, DbErr> {
+    let _timer = QueryTimer::start("update_movie");
+
+    movie::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {id} not found"
+        )))?;
+
+    let movie = data.into_active_model(id).update(db).await?;
+    changes.changed(Change::MovieSaved(movie.clone()));
+
+    Ok(movie)
+}
+
+#[instrument(skip_all)]
+pub async fn update_movie_partial(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    id: i32,
+    data: PartialMovie,
+) -> Result<movie::Model, DbErr> {
+    let _timer = QueryTimer::start("update_movie_partial");
+
+    movie::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {id} not found"
+        )))?;
+
+    let movie = data.into_active_model(id).update(db).await?;
+    changes.changed(Change::MovieSaved(movie.clone()));
+
+    Ok(movie)
+}
+
+#[instrument(skip_all)]
+pub async fn create_person(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    data: person::Model,
+) -> Result<person::Model, DbErr> {
+    let _timer = QueryTimer::start("create_person");
+
+    let active_person = person::ActiveModel {
+        name: Set(data.name.trim().to_owned()),
         ..Default::default()
     };
 
-    active_movie.save(db).await?.try_into()
+    let person: person::Model = active_person.save(db).await?.try_into()?;
+    changes.changed(Change::PersonSaved(person.clone()));
+
+    Ok(person)
 }
 
-pub async fn delete_movie(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
-    movie::Entity::delete_by_id(id).exec(db).await
+#[instrument(skip_all)]
+pub async fn delete_person(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    id: i32,
+) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_person");
+
+    let result = person::Entity::delete_by_id(id).exec(db).await?;
+    if result.rows_affected > 0 {
+        changes.changed(Change::PersonDeleted(id));
+    }
+
+    Ok(result)
 }
 
-pub async fn update_movie(db: &DbConn, id: i32, data: movie::Model) -> Result<movie::Model, DbErr> {
-    let active_movie: movie::ActiveModel = movie::Entity::find_by_id(id)
+#[instrument(skip_all)]
+pub async fn update_person(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    id: i32,
+    data: person::Model,
+) -> Result<person::Model, DbErr> {
+    let _timer = QueryTimer::start("update_person");
+
+    let active_person: person::ActiveModel = person::Entity::find_by_id(id)
         .one(db)
         .await?
         .ok_or(DbErr::RecordNotFound(format!(
-            "Movie with id {id} not found"
+            "Person with id {id} not found"
         )))?
         .into();
 
-    movie::ActiveModel {
-        id: active_movie.id,
-        title: Set(data.title),
-        release_date: Set(data.release_date),
-        poster_url: Set(data.poster_url),
-        description: Set(data.description),
-        rating: Set(data.rating),
+    let person = person::ActiveModel {
+        id: active_person.id,
+        name: Set(data.name.trim().to_owned()),
     }
     .update(db)
-    .await
+    .await?;
+    changes.changed(Change::PersonSaved(person.clone()));
+
+    Ok(person)
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
-pub struct PartialMovie {
-    pub title: Option<String>,
-    pub release_date: Option<chrono::DateTime<chrono::Utc>>,
-    pub poster_url: Option<String>,
-    pub description: Option<String>,
-    pub rating: Option<i32>,
+pub struct PartialPerson {
+    pub name: Option<String>,
 }
 
-fn option_into_active_value<T>(maybe_value: Option<T>) -> ActiveValue<T>
-where
-    T: Into<Value>,
-{
-    match maybe_value {
-        Some(value) => Set(value),
-        None => NotSet,
+#[instrument(skip_all)]
+pub async fn update_person_partial(
+    db: &DbConn,
+    changes: &dyn ChangeListener,
+    id: i32,
+    data: PartialPerson,
+) -> Result<person::Model, DbErr> {
+    let _timer = QueryTimer::start("update_person_partial");
+
+    let active_person: person::ActiveModel = person::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Person with id {id} not found"
+        )))?
+        .into();
+
+    let person = person::ActiveModel {
+        id: active_person.id,
+        name: option_into_active_value(data.name.map(|name| name.trim().to_owned())),
     }
+    .update(db)
+    .await?;
+    changes.changed(Change::PersonSaved(person.clone()));
+
+    Ok(person)
 }
 
-pub async fn update_movie_partial(
+/// Credit a person on a movie, failing with [`DbErr::RecordNotFound`] if either does not exist.
+#[instrument(skip_all)]
+pub async fn create_credit(db: &DbConn, data: credit::Model) -> Result<credit::Model, DbErr> {
+    let _timer = QueryTimer::start("create_credit");
+
+    let txn = db.begin().await?;
+
+    movie::Entity::find_by_id(data.movie_id)
+        .one(&txn)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {} not found",
+            data.movie_id
+        )))?;
+
+    person::Entity::find_by_id(data.person_id)
+        .one(&txn)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Person with id {} not found",
+            data.person_id
+        )))?;
+
+    let credit = credit::ActiveModel {
+        movie_id: Set(data.movie_id),
+        person_id: Set(data.person_id),
+        r#type: Set(data.r#type),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(credit)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_credit(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_credit");
+
+    credit::Entity::delete_by_id(id).exec(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn create_genre(db: &DbConn, data: CreateGenre) -> Result<genre::Model, DbErr> {
+    let _timer = QueryTimer::start("create_genre");
+
+    data.into_active_model().insert(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn update_genre(db: &DbConn, id: i32, data: CreateGenre) -> Result<genre::Model, DbErr> {
+    let _timer = QueryTimer::start("update_genre");
+
+    genre::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Genre with id {id} not found"
+        )))?;
+
+    genre::ActiveModel {
+        id: Unchanged(id),
+        ..data.into_active_model()
+    }
+    .update(db)
+    .await
+}
+
+#[instrument(skip_all)]
+pub async fn delete_genre(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_genre");
+
+    genre::Entity::delete_by_id(id).exec(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn create_keyword(db: &DbConn, data: CreateKeyword) -> Result<keyword::Model, DbErr> {
+    let _timer = QueryTimer::start("create_keyword");
+
+    data.into_active_model().insert(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn update_keyword(
     db: &DbConn,
     id: i32,
-    data: PartialMovie,
-) -> Result<movie::Model, DbErr> {
-    let active_movie: movie::ActiveModel = movie::Entity::find_by_id(id)
+    data: CreateKeyword,
+) -> Result<keyword::Model, DbErr> {
+    let _timer = QueryTimer::start("update_keyword");
+
+    keyword::Entity::find_by_id(id)
         .one(db)
         .await?
         .ok_or(DbErr::RecordNotFound(format!(
-            "Movie with id {id} not found"
-        )))?
-        .into();
+            "Keyword with id {id} not found"
+        )))?;
 
-    movie::ActiveModel {
-        id: active_movie.id,
-        title: option_into_active_value(data.title),
-        release_date: option_into_active_value(data.release_date),
-        poster_url: option_into_active_value(data.poster_url),
-        description: option_into_active_value(data.description),
-        rating: option_into_active_value(data.rating),
+    keyword::ActiveModel {
+        id: Unchanged(id),
+        ..data.into_active_model()
     }
     .update(db)
     .await
 }
+
+#[instrument(skip_all)]
+pub async fn delete_keyword(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_keyword");
+
+    keyword::Entity::delete_by_id(id).exec(db).await
+}
+
+/// Assign a genre to a movie, doing nothing if it already has it and failing with
+/// [`DbErr::RecordNotFound`] if either does not exist.
+#[instrument(skip_all)]
+pub async fn assign_genre(db: &DbConn, movie_id: i32, genre_id: i32) -> Result<(), DbErr> {
+    let _timer = QueryTimer::start("assign_genre");
+
+    find_movie(db, movie_id).await?;
+    genre::Entity::find_by_id(genre_id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Genre with id {genre_id} not found"
+        )))?;
+
+    let assigned = movie_genre::Entity::insert(movie_genre::ActiveModel {
+        movie_id: Set(movie_id),
+        genre_id: Set(genre_id),
+    })
+    .on_conflict(
+        OnConflict::columns([movie_genre::Column::MovieId, movie_genre::Column::GenreId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec(db)
+    .await;
+
+    match assigned {
+        Ok(_) | Err(DbErr::RecordNotInserted) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn unassign_genre(
+    db: &DbConn,
+    movie_id: i32,
+    genre_id: i32,
+) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("unassign_genre");
+
+    movie_genre::Entity::delete_by_id((movie_id, genre_id))
+        .exec(db)
+        .await
+}
+
+/// Assign a keyword to a movie, doing nothing if it already has it and failing with
+/// [`DbErr::RecordNotFound`] if either does not exist.
+#[instrument(skip_all)]
+pub async fn assign_keyword(db: &DbConn, movie_id: i32, keyword_id: i32) -> Result<(), DbErr> {
+    let _timer = QueryTimer::start("assign_keyword");
+
+    find_movie(db, movie_id).await?;
+    keyword::Entity::find_by_id(keyword_id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Keyword with id {keyword_id} not found"
+        )))?;
+
+    let assigned = movie_keyword::Entity::insert(movie_keyword::ActiveModel {
+        movie_id: Set(movie_id),
+        keyword_id: Set(keyword_id),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            movie_keyword::Column::MovieId,
+            movie_keyword::Column::KeywordId,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec(db)
+    .await;
+
+    match assigned {
+        Ok(_) | Err(DbErr::RecordNotInserted) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+#[instrument(skip_all)]
+pub async fn unassign_keyword(
+    db: &DbConn,
+    movie_id: i32,
+    keyword_id: i32,
+) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("unassign_keyword");
+
+    movie_keyword::Entity::delete_by_id((movie_id, keyword_id))
+        .exec(db)
+        .await
+}
+
+async fn find_movie(db: &DbConn, id: i32) -> Result<movie::Model, DbErr> {
+    movie::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {id} not found"
+        )))
+}
+
+/// A newly created API key, along with the key itself which is not stored and cannot be
+/// retrieved later.
+#[derive(Debug, Clone)]
+pub struct NewApiKey {
+    pub api_key: api_key::Model,
+    pub key: String,
+}
+
+#[instrument(skip_all)]
+pub async fn create_api_key(
+    db: &DbConn,
+    name: String,
+    scope: ApiKeyScope,
+) -> Result<NewApiKey, DbErr> {
+    let _timer = QueryTimer::start("create_api_key");
+
+    let (key, prefix) = generate_api_key();
+
+    let api_key = api_key::ActiveModel {
+        name: Set(name),
+        prefix: Set(prefix),
+        key_hash: Set(hash_token(&key)),
+        scope: Set(scope),
+        created_at: Set(chrono::Utc::now()),
+        revoked_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(NewApiKey { api_key, key })
+}
+
+/// Revoke an API key so that it is no longer accepted, keeping the time it was first revoked at.
+#[instrument(skip_all)]
+pub async fn revoke_api_key(db: &DbConn, id: i32) -> Result<api_key::Model, DbErr> {
+    let _timer = QueryTimer::start("revoke_api_key");
+
+    let api_key = api_key::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "API key with id {id} not found"
+        )))?;
+
+    if api_key.revoked_at.is_some() {
+        return Ok(api_key);
+    }
+
+    let mut active_api_key: api_key::ActiveModel = api_key.into();
+    active_api_key.revoked_at = Set(Some(chrono::Utc::now()));
+
+    active_api_key.update(db).await
+}
+
+/// Change the role of a user, which an OpenID Connect login overrides if the provider gives
+/// roles.
+#[instrument(skip_all)]
+pub async fn update_user_role(db: &DbConn, id: i32, role: UserRole) -> Result<user::Model, DbErr> {
+    let _timer = QueryTimer::start("update_user_role");
+
+    let user = user::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "User with id {id} not found"
+        )))?;
+
+    let mut active_user: user::ActiveModel = user.into();
+    active_user.role = Set(role);
+
+    active_user.update(db).await
+}
+
+/// Delete a user along with their sessions and ratings, which no longer count towards the
+/// histograms of the movies they rated.
+#[instrument(skip_all)]
+pub async fn delete_user(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_user");
+
+    let txn = db.begin().await?;
+
+    // By movie, so that movies are always locked in the same order
+    let ratings = user_rating::Entity::find()
+        .filter(user_rating::Column::UserId.eq(id))
+        .order_by_asc(user_rating::Column::MovieId)
+        .all(&txn)
+        .await?;
+    for rating in ratings {
+        if let Some(movie) = lock_movie(&txn, rating.movie_id).await? {
+            update_histogram(&txn, movie, |histogram| {
+                count_rating(histogram, rating.score, -1)
+            })
+            .await?;
+        }
+    }
+
+    let result = user::Entity::delete_by_id(id).exec(&txn).await?;
+    txn.commit().await?;
+
+    Ok(result)
+}
+
+/// Create a user account, failing with a unique constraint violation if the email is taken.
+///
+/// The password must already be hashed with [`hash_password`](crate::hash_password).
+#[instrument(skip_all)]
+pub async fn create_user(
+    db: &DbConn,
+    email: &str,
+    display_name: String,
+    password_hash: String,
+) -> Result<user::Model, DbErr> {
+    let _timer = QueryTimer::start("create_user");
+
+    user::ActiveModel {
+        email: Set(normalize_email(email)),
+        display_name: Set(display_name.trim().to_owned()),
+        password_hash: Set(Some(password_hash)),
+        role: Set(UserRole::default()),
+        created_at: Set(chrono::Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Find or create the local user of an OpenID Connect identity, updating it from the claims of
+/// the provider.
+///
+/// An identity is linked to an existing user without one when the provider verified that both
+/// have the same email address, otherwise a new user is created, which fails with a unique
+/// constraint violation if the email is taken.
+///
+/// As registering does not verify email addresses, the user may have been registered by someone
+/// else than the owner of the address: linking removes its password and logs out its sessions, so
+/// that only the identity gives access to it from then on.
+#[instrument(skip_all)]
+pub async fn upsert_oidc_user(db: &DbConn, identity: OidcIdentity) -> Result<user::Model, DbErr> {
+    let _timer = QueryTimer::start("upsert_oidc_user");
+
+    let email = normalize_email(&identity.email);
+    let txn = db.begin().await?;
+
+    let linked = user::Entity::find()
+        .filter(user::Column::OidcIssuer.eq(&identity.issuer))
+        .filter(user::Column::OidcSubject.eq(&identity.subject))
+        .one(&txn)
+        .await?;
+
+    let existing = match linked {
+        Some(user) => Some(user),
+        None if identity.email_verified => {
+            user::Entity::find()
+                .filter(user::Column::Email.eq(&email))
+                .filter(user::Column::OidcSubject.is_null())
+                .one(&txn)
+                .await?
+        }
+        None => None,
+    };
+
+    let user = match existing {
+        Some(user) => {
+            let role = identity.role.unwrap_or(user.role);
+            let linking = user.oidc_subject.is_none();
+            if linking {
+                session::Entity::delete_many()
+                    .filter(session::Column::UserId.eq(user.id))
+                    .exec(&txn)
+                    .await?;
+            }
+
+            let mut active_user: user::ActiveModel = user.into();
+            if linking {
+                active_user.password_hash = Set(None);
+            }
+            active_user.email = Set(email);
+            active_user.display_name = Set(identity.display_name);
+            active_user.role = Set(role);
+            active_user.oidc_issuer = Set(Some(identity.issuer));
+            active_user.oidc_subject = Set(Some(identity.subject));
+
+            active_user.update(&txn).await?
+        }
+        None => {
+            user::ActiveModel {
+                email: Set(email),
+                display_name: Set(identity.display_name),
+                password_hash: Set(None),
+                role: Set(identity.role.unwrap_or_default()),
+                oidc_issuer: Set(Some(identity.issuer)),
+                oidc_subject: Set(Some(identity.subject)),
+                created_at: Set(chrono::Utc::now()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?
+        }
+    };
+
+    txn.commit().await?;
+
+    Ok(user)
+}
+
+/// A newly created session, along with the token identifying it which is only stored hashed.
+#[derive(Debug, Clone)]
+pub struct NewSession {
+    pub session: session::Model,
+    pub token: String,
+}
+
+/// Log a user in for `ttl`, deleting the sessions of every user that expired meanwhile.
+#[instrument(skip_all)]
+pub async fn create_session(
+    db: &DbConn,
+    user_id: i32,
+    ttl: chrono::Duration,
+) -> Result<NewSession, DbErr> {
+    let _timer = QueryTimer::start("create_session");
+
+    let now = chrono::Utc::now();
+
+    session::Entity::delete_many()
+        .filter(session::Column::ExpiresAt.lte(now))
+        .exec(db)
+        .await?;
+
+    let token = generate_session_token();
+
+    let session = session::ActiveModel {
+        token_hash: Set(hash_token(&token)),
+        user_id: Set(user_id),
+        created_at: Set(now),
+        expires_at: Set(now + ttl),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(NewSession { session, token })
+}
+
+#[instrument(skip_all)]
+pub async fn delete_session(db: &DbConn, token: &str) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_session");
+
+    session::Entity::delete_many()
+        .filter(session::Column::TokenHash.eq(hash_token(token)))
+        .exec(db)
+        .await
+}
+
+/// Rate a movie for a user, replacing the score they gave it before, and count it in the histogram
+/// of the movie.
+///
+/// Change listeners are not told, neither the search index nor the suggestions holding ratings.
+#[instrument(skip_all)]
+pub async fn rate_movie(
+    db: &DbConn,
+    user_id: i32,
+    movie_id: i32,
+    score: i32,
+) -> Result<user_rating::Model, DbErr> {
+    let _timer = QueryTimer::start("rate_movie");
+
+    let txn = db.begin().await?;
+
+    let movie = lock_movie(&txn, movie_id)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {movie_id} not found"
+        )))?;
+    let previous = user_rating::Entity::find_by_id((user_id, movie_id))
+        .one(&txn)
+        .await?;
+
+    let rating = user_rating::ActiveModel {
+        user_id: Set(user_id),
+        movie_id: Set(movie_id),
+        score: Set(score),
+        rated_at: Set(chrono::Utc::now()),
+    };
+    let rating = match &previous {
+        Some(_) => rating.update(&txn).await?,
+        None => rating.insert(&txn).await?,
+    };
+
+    update_histogram(&txn, movie, |histogram| {
+        if let Some(previous) = previous {
+            count_rating(histogram, previous.score, -1);
+        }
+        count_rating(histogram, score, 1);
+    })
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(rating)
+}
+
+/// Remove the rating a user gave a movie, and its count in the histogram of the movie.
+#[instrument(skip_all)]
+pub async fn unrate_movie(db: &DbConn, user_id: i32, movie_id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("unrate_movie");
+
+    let txn = db.begin().await?;
+
+    let Some(movie) = lock_movie(&txn, movie_id).await? else {
+        return Ok(DeleteResult { rows_affected: 0 });
+    };
+    let Some(rating) = user_rating::Entity::find_by_id((user_id, movie_id))
+        .one(&txn)
+        .await?
+    else {
+        return Ok(DeleteResult { rows_affected: 0 });
+    };
+
+    let result = rating.clone().delete(&txn).await?;
+    update_histogram(&txn, movie, |histogram| {
+        count_rating(histogram, rating.score, -1)
+    })
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(result)
+}
+
+/// Get a movie and lock it until the end of the transaction, so that concurrent ratings don't
+/// lose updates to its histogram.
+async fn lock_movie(
+    txn: &DatabaseTransaction,
+    movie_id: i32,
+) -> Result<Option<movie::Model>, DbErr> {
+    movie::Entity::find_by_id(movie_id)
+        .lock_exclusive()
+        .one(txn)
+        .await
+}
+
+async fn update_histogram(
+    txn: &DatabaseTransaction,
+    movie: movie::Model,
+    update: impl FnOnce(&mut RatingHistogram),
+) -> Result<(), DbErr> {
+    let mut histogram = movie.user_rating_histogram.clone();
+    update(&mut histogram);
+
+    let mut active_movie: movie::ActiveModel = movie.into();
+    active_movie.user_rating_histogram = Set(histogram);
+    active_movie.update(txn).await?;
+
+    Ok(())
+}
+
+fn count_rating(histogram: &mut RatingHistogram, score: i32, change: i32) {
+    let Ok(score) = usize::try_from(score) else {
+        return;
+    };
+
+    if histogram.0.len() <= score {
+        histogram.0.resize(score + 1, 0);
+    }
+    histogram.0[score] += change;
+
+    while histogram.0.last() == Some(&0) {
+        histogram.0.pop();
+    }
+}
+
+/// Create a list owned by a user, without any movie on it yet.
+#[instrument(skip_all)]
+pub async fn create_list(
+    db: &DbConn,
+    owner_id: i32,
+    data: CreateList,
+) -> Result<list::Model, DbErr> {
+    let _timer = QueryTimer::start("create_list");
+
+    let now = chrono::Utc::now();
+
+    list::ActiveModel {
+        owner_id: Set(owner_id),
+        name: Set(data.name),
+        description: Set(data.description),
+        visibility: Set(data.visibility),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+#[instrument(skip_all)]
+pub async fn update_list(db: &DbConn, id: i32, data: PartialList) -> Result<list::Model, DbErr> {
+    let _timer = QueryTimer::start("update_list");
+
+    let list = list::Entity::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "List with id {id} not found"
+        )))?;
+
+    let mut active_list: list::ActiveModel = list.into();
+    active_list.name = option_into_active_value(data.name);
+    active_list.description = option_into_active_value(data.description);
+    active_list.visibility = option_into_active_value(data.visibility);
+    active_list.updated_at = Set(chrono::Utc::now());
+
+    active_list.update(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn delete_list(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_list");
+
+    list::Entity::delete_by_id(id).exec(db).await
+}
+
+/// Put a movie on a list, at the given position or else at the end, failing with a unique
+/// constraint violation if it already is on the list.
+#[instrument(skip_all)]
+pub async fn add_list_item(
+    db: &DbConn,
+    list_id: i32,
+    data: AddListItem,
+) -> Result<list_item::Model, DbErr> {
+    let _timer = QueryTimer::start("add_list_item");
+
+    let txn = db.begin().await?;
+
+    let list = lock_list(&txn, list_id)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "List with id {list_id} not found"
+        )))?;
+    movie::Entity::find_by_id(data.movie_id)
+        .one(&txn)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {} not found",
+            data.movie_id
+        )))?;
+
+    let len = list_item::Entity::find()
+        .filter(list_item::Column::ListId.eq(list_id))
+        .count(&txn)
+        .await?;
+    let len = i32::try_from(len).unwrap_or(i32::MAX);
+    let position = data.position.map_or(len, |position| position.min(len));
+
+    list_item::Entity::update_many()
+        .col_expr(
+            list_item::Column::Position,
+            Expr::col(list_item::Column::Position).add(1),
+        )
+        .filter(list_item::Column::ListId.eq(list_id))
+        .filter(list_item::Column::Position.gte(position))
+        .exec(&txn)
+        .await?;
+
+    let item = list_item::ActiveModel {
+        list_id: Set(list_id),
+        movie_id: Set(data.movie_id),
+        position: Set(position),
+        note: Set(data.note),
+        added_at: Set(chrono::Utc::now()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    touch_list(&txn, list).await?;
+    txn.commit().await?;
+
+    Ok(item)
+}
+
+#[instrument(skip_all)]
+pub async fn update_list_item(
+    db: &DbConn,
+    list_id: i32,
+    item_id: i32,
+    data: UpdateListItem,
+) -> Result<list_item::Model, DbErr> {
+    let _timer = QueryTimer::start("update_list_item");
+
+    let txn = db.begin().await?;
+
+    let list = lock_list(&txn, list_id)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "List with id {list_id} not found"
+        )))?;
+    let item = find_list_item(&txn, list_id, item_id)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Item with id {item_id} not found on list {list_id}"
+        )))?;
+
+    let mut active_item: list_item::ActiveModel = item.into();
+    active_item.note = Set(data.note);
+    let item = active_item.update(&txn).await?;
+
+    touch_list(&txn, list).await?;
+    txn.commit().await?;
+
+    Ok(item)
+}
+
+/// Take a movie off a list, moving the ones after it up.
+#[instrument(skip_all)]
+pub async fn remove_list_item(
+    db: &DbConn,
+    list_id: i32,
+    item_id: i32,
+) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("remove_list_item");
+
+    let txn = db.begin().await?;
+
+    let Some(list) = lock_list(&txn, list_id).await? else {
+        return Ok(DeleteResult { rows_affected: 0 });
+    };
+    let Some(item) = find_list_item(&txn, list_id, item_id).await? else {
+        return Ok(DeleteResult { rows_affected: 0 });
+    };
+
+    let position = item.position;
+    let result = item.delete(&txn).await?;
+
+    list_item::Entity::update_many()
+        .col_expr(
+            list_item::Column::Position,
+            Expr::col(list_item::Column::Position).sub(1),
+        )
+        .filter(list_item::Column::ListId.eq(list_id))
+        .filter(list_item::Column::Position.gt(position))
+        .exec(&txn)
+        .await?;
+
+    touch_list(&txn, list).await?;
+    txn.commit().await?;
+
+    Ok(result)
+}
+
+/// Put the items of a list in the order of `data`, returning them in that order, or `None` if
+/// `data` does not list every item of the list exactly once.
+#[instrument(skip_all)]
+pub async fn reorder_list(
+    db: &DbConn,
+    list_id: i32,
+    data: ReorderList,
+) -> Result<Option<Vec<list_item::Model>>, DbErr> {
+    let _timer = QueryTimer::start("reorder_list");
+
+    let txn = db.begin().await?;
+
+    let list = lock_list(&txn, list_id)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "List with id {list_id} not found"
+        )))?;
+    let mut items = list_item::Entity::find()
+        .filter(list_item::Column::ListId.eq(list_id))
+        .all(&txn)
+        .await?;
+
+    let mut current_ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+    let mut new_ids = data.item_ids.clone();
+    current_ids.sort_unstable();
+    new_ids.sort_unstable();
+    if current_ids != new_ids {
+        return Ok(None);
+    }
+
+    items.sort_by_key(|item| {
+        data.item_ids
+            .iter()
+            .position(|&id| id == item.id)
+            .unwrap_or(usize::MAX)
+    });
+    let mut reordered = Vec::with_capacity(items.len());
+    for (position, item) in (0..).zip(items) {
+        if item.position == position {
+            reordered.push(item);
+            continue;
+        }
+
+        let mut active_item: list_item::ActiveModel = item.into();
+        active_item.position = Set(position);
+        reordered.push(active_item.update(&txn).await?);
+    }
+
+    touch_list(&txn, list).await?;
+    txn.commit().await?;
+
+    Ok(Some(reordered))
+}
+
+/// Write a review of a movie, pending moderation.
+#[instrument(skip_all)]
+pub async fn create_review(
+    db: &DbConn,
+    author_id: i32,
+    data: CreateReview,
+) -> Result<review::Model, DbErr> {
+    let _timer = QueryTimer::start("create_review");
+
+    movie::Entity::find_by_id(data.movie_id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Movie with id {} not found",
+            data.movie_id
+        )))?;
+
+    let now = chrono::Utc::now();
+
+    review::ActiveModel {
+        movie_id: Set(data.movie_id),
+        author_id: Set(author_id),
+        title: Set(data.title),
+        body_html: Set(render_markdown(&data.body)),
+        body: Set(data.body),
+        spoiler: Set(data.spoiler),
+        status: Set(ReviewStatus::Pending),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+}
+
+/// Edit a review, which sends it back to moderation whatever its status.
+#[instrument(skip_all)]
+pub async fn update_review(
+    db: &DbConn,
+    id: i32,
+    data: PartialReview,
+) -> Result<review::Model, DbErr> {
+    let _timer = QueryTimer::start("update_review");
+
+    let txn = db.begin().await?;
+
+    let review = lock_review(&txn, id).await?;
+
+    let mut active_review: review::ActiveModel = review.into();
+    active_review.title = option_into_active_value(data.title);
+    if let Some(body) = data.body {
+        active_review.body_html = Set(render_markdown(&body));
+        active_review.body = Set(body);
+    }
+    active_review.spoiler = option_into_active_value(data.spoiler);
+    active_review.status = Set(ReviewStatus::Pending);
+    active_review.updated_at = Set(chrono::Utc::now());
+
+    let review = active_review.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(review)
+}
+
+#[instrument(skip_all)]
+pub async fn delete_review(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+    let _timer = QueryTimer::start("delete_review");
+
+    review::Entity::delete_by_id(id).exec(db).await
+}
+
+/// Move a review to the status a moderator decided on, returning `None` without changing it if
+/// its current status cannot become that one.
+#[instrument(skip_all)]
+pub async fn moderate_review(
+    db: &DbConn,
+    id: i32,
+    status: ReviewStatus,
+) -> Result<Option<review::Model>, DbErr> {
+    let _timer = QueryTimer::start("moderate_review");
+
+    let txn = db.begin().await?;
+
+    let review = lock_review(&txn, id).await?;
+    if !review.status.may_become(status) {
+        return Ok(None);
+    }
+
+    let mut active_review: review::ActiveModel = review.into();
+    active_review.status = Set(status);
+
+    let review = active_review.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(Some(review))
+}
+
+/// Get a review and lock it until the end of the transaction, so that moderators decide on the
+/// version its author last wrote.
+async fn lock_review(txn: &DatabaseTransaction, id: i32) -> Result<review::Model, DbErr> {
+    review::Entity::find_by_id(id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!(
+            "Review with id {id} not found"
+        )))
+}
+
+/// Get a list and lock it until the end of the transaction, so that concurrent changes to its
+/// items keep their positions without gaps or duplicates.
+async fn lock_list(txn: &DatabaseTransaction, list_id: i32) -> Result<Option<list::Model>, DbErr> {
+    list::Entity::find_by_id(list_id)
+        .lock_exclusive()
+        .one(txn)
+        .await
+}
+
+async fn find_list_item(
+    txn: &DatabaseTransaction,
+    list_id: i32,
+    item_id: i32,
+) -> Result<Option<list_item::Model>, DbErr> {
+    list_item::Entity::find_by_id(item_id)
+        .filter(list_item::Column::ListId.eq(list_id))
+        .one(txn)
+        .await
+}
+
+async fn touch_list(txn: &DatabaseTransaction, list: list::Model) -> Result<(), DbErr> {
+    let mut active_list: list::ActiveModel = list.into();
+    active_list.updated_at = Set(chrono::Utc::now());
+    active_list.update(txn).await?;
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use sea_orm::*;
+
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// How to slice a list of rows into a page.
+///
+/// Offset pagination is simple but gets slower as the offset grows, cursor (keyset) pagination
+/// is stable under inserts and only ever scans `limit` rows past the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pagination {
+    Offset { limit: u64, offset: u64 },
+    After { limit: u64, id: i32 },
+    Before { limit: u64, id: i32 },
+}
+
+impl Pagination {
+    pub fn limit(&self) -> u64 {
+        match *self {
+            Pagination::Offset { limit, .. }
+            | Pagination::After { limit, .. }
+            | Pagination::Before { limit, .. } => limit,
+        }
+    }
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Pagination::Offset {
+            limit: DEFAULT_PAGE_SIZE,
+            offset: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub page_size: u64,
+    pub next: Option<Pagination>,
+    pub prev: Option<Pagination>,
+}
+
+impl<T> Page<T> {
+    /// Replace the items of this page, keeping its metadata.
+    pub fn with_items<U>(self, items: Vec<U>) -> Page<U> {
+        Page {
+            items,
+            total: self.total,
+            page_size: self.page_size,
+            next: self.next,
+            prev: self.prev,
+        }
+    }
+}
+
+/// Fetch one page of `select`, keyed on the integer primary key `id_column`.
+///
+/// For offset pagination the ordering of `select` is kept as is. Cursor pagination replaces it
+/// with an ordering on `id_column`, which is what the cursor is keyed on.
+pub(crate) async fn paginate<E>(
+    db: &DbConn,
+    select: Select<E>,
+    id_column: E::Column,
+    id_of: impl Fn(&E::Model) -> i32,
+    pagination: Pagination,
+) -> Result<Page<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let total = select.clone().count(db).await?;
+    let limit = pagination.limit();
+
+    let (items, next, prev) = match pagination {
+        Pagination::Offset { limit, offset } => {
+            let items = select.offset(offset).limit(limit).all(db).await?;
+
+            let next = (offset + limit < total).then_some(Pagination::Offset {
+                limit,
+                offset: offset + limit,
+            });
+            let prev = (offset > 0).then_some(Pagination::Offset {
+                limit,
+                offset: offset.saturating_sub(limit),
+            });
+
+            (items, next, prev)
+        }
+        Pagination::After { limit, id } => {
+            let select = unordered(select);
+            let mut items = select
+                .clone()
+                .filter(id_column.gt(id))
+                .order_by_asc(id_column)
+                .limit(limit + 1)
+                .all(db)
+                .await?;
+
+            let has_next = items.len() as u64 > limit;
+            items.truncate(limit as usize);
+
+            let next = has_next
+                .then(|| items.last().map(&id_of))
+                .flatten()
+                .map(|id| Pagination::After { limit, id });
+            let prev = match items.first().map(&id_of) {
+                Some(first_id) => exists(db, select, id_column.lt(first_id)).await?.then_some(
+                    Pagination::Before {
+                        limit,
+                        id: first_id,
+                    },
+                ),
+                None => None,
+            };
+
+            (items, next, prev)
+        }
+        Pagination::Before { limit, id } => {
+            let select = unordered(select);
+            let mut items = select
+                .clone()
+                .filter(id_column.lt(id))
+                .order_by_desc(id_column)
+                .limit(limit + 1)
+                .all(db)
+                .await?;
+
+            let has_prev = items.len() as u64 > limit;
+            items.truncate(limit as usize);
+            items.reverse();
+
+            let prev = has_prev
+                .then(|| items.first().map(&id_of))
+                .flatten()
+                .map(|id| Pagination::Before { limit, id });
+            let next = match items.last().map(&id_of) {
+                Some(last_id) => exists(db, select, id_column.gt(last_id))
+                    .await?
+                    .then_some(Pagination::After { limit, id: last_id }),
+                None => None,
+            };
+
+            (items, next, prev)
+        }
+    };
+
+    Ok(Page {
+        items,
+        total,
+        page_size: limit,
+        next,
+        prev,
+    })
+}
+
+fn unordered<E>(mut select: Select<E>) -> Select<E>
+where
+    E: EntityTrait,
+{
+    QueryTrait::query(&mut select).clear_order_by();
+    select
+}
+
+async fn exists<E>(
+    db: &DbConn,
+    select: Select<E>,
+    condition: sea_query::SimpleExpr,
+) -> Result<bool, DbErr>
+where
+    E: EntityTrait,
+{
+    Ok(select.filter(condition).one(db).await?.is_some())
+}
@@ -0,0 +1,23 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+/// Hash a password with Argon2id and a random salt, in the PHC string format.
+///
+/// This takes tens of milliseconds on purpose, so call it off the async runtime.
+pub fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut rand::thread_rng());
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("hashing with the default Argon2 parameters cannot fail")
+        .to_string()
+}
+
+/// Whether `password` matches a hash made by [`hash_password`].
+///
+/// Like [`hash_password`], this is slow on purpose.
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .and_then(|hash| Argon2::default().verify_password(password.as_bytes(), &hash))
+        .is_ok()
+}
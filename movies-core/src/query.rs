@@ -1,10 +1,711 @@
-use ::movies_entity::movie;
+use ::movies_entity::sea_orm_active_enums::{CreditType, ListVisibility, ReviewStatus};
+use ::movies_entity::{
+    api_key, credit, genre, keyword, list, list_item, movie, movie_genre, movie_keyword, person,
+    review, session, user, user_rating,
+};
+use sea_orm::sea_query::{Expr, Func, IntoIden, LikeExpr, Query, SelectStatement};
 use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use utoipa::ToSchema;
 
-pub async fn get_all_movies(db: &DbConn) -> Result<Vec<movie::Model>, DbErr> {
-    movie::Entity::find().all(db).await
+use crate::dto::{normalize_email, ListItemResponse, MovieResponse};
+use crate::pagination::{paginate, Page, Pagination};
+use crate::timing::QueryTimer;
+use crate::tokens::hash_token;
+
+/// Conditions a movie must match to be listed. Every condition is optional and they are combined
+/// with `AND`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MovieFilter {
+    pub released_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub released_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub min_rating: Option<i32>,
+    pub max_rating: Option<i32>,
+    /// Case-insensitive substring of the title
+    pub title: Option<String>,
+    pub genres: Option<TaxonomyFilter>,
+    pub keywords: Option<TaxonomyFilter>,
+}
+
+/// Whether movies must have any or all of the genres or keywords of a [`TaxonomyFilter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaxonomyMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// Genres or keywords a movie must have to be listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaxonomyFilter {
+    pub ids: Vec<i32>,
+    pub matching: TaxonomyMatch,
+}
+
+impl TaxonomyFilter {
+    /// Select the ids of the movies matching the filter from a join table.
+    fn movie_ids<C>(&self, table: impl IntoIden + 'static, movie_id: C, id: C) -> SelectStatement
+    where
+        C: IntoIden + Copy + 'static,
+    {
+        let mut ids = self.ids.clone();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let mut select = Query::select();
+        select
+            .column(movie_id)
+            .from(table)
+            .and_where(Expr::col(id).is_in(ids.iter().copied()));
+
+        if self.matching == TaxonomyMatch::All {
+            select
+                .group_by_col(movie_id)
+                .and_having(Expr::col(id).count_distinct().eq(ids.len() as i64));
+        }
+
+        select
+    }
+}
+
+impl MovieFilter {
+    fn condition(&self) -> Condition {
+        Condition::all()
+            .add_option(
+                self.released_after
+                    .map(|date| movie::Column::ReleaseDate.gte(date)),
+            )
+            .add_option(
+                self.released_before
+                    .map(|date| movie::Column::ReleaseDate.lte(date)),
+            )
+            .add_option(
+                self.min_rating
+                    .map(|rating| movie::Column::Rating.gte(rating)),
+            )
+            .add_option(
+                self.max_rating
+                    .map(|rating| movie::Column::Rating.lte(rating)),
+            )
+            .add_option(self.title.as_deref().map(|title| {
+                Expr::expr(Func::lower(Expr::col(movie::Column::Title))).like(
+                    LikeExpr::new(format!("%{}%", escape_like(&title.to_lowercase()))).escape('\\'),
+                )
+            }))
+            .add_option(self.genres.as_ref().map(|genres| {
+                movie::Column::Id.in_subquery(genres.movie_ids(
+                    movie_genre::Entity,
+                    movie_genre::Column::MovieId,
+                    movie_genre::Column::GenreId,
+                ))
+            }))
+            .add_option(self.keywords.as_ref().map(|keywords| {
+                movie::Column::Id.in_subquery(keywords.movie_ids(
+                    movie_keyword::Entity,
+                    movie_keyword::Column::MovieId,
+                    movie_keyword::Column::KeywordId,
+                ))
+            }))
+    }
+}
+
+pub(crate) fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Asc => Order::Asc,
+            SortOrder::Desc => Order::Desc,
+        }
+    }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct MovieSort {
+    pub column: movie::Column,
+    pub order: SortOrder,
+}
+
+impl MovieSort {
+    pub fn is_default(&self) -> bool {
+        matches!(self.column, movie::Column::Id) && self.order == SortOrder::Asc
+    }
+}
+
+impl Default for MovieSort {
+    fn default() -> Self {
+        MovieSort {
+            column: movie::Column::Id,
+            order: SortOrder::Asc,
+        }
+    }
+}
+
+/// List the movies matching `filter`.
+///
+/// Cursor pagination is keyed on the movie id and ignores `sort`, which only applies to offset
+/// pagination.
+#[instrument(skip_all)]
+pub async fn get_all_movies(
+    db: &DbConn,
+    filter: &MovieFilter,
+    sort: MovieSort,
+    pagination: Pagination,
+) -> Result<Page<movie::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_all_movies");
+
+    let mut select = movie::Entity::find()
+        .filter(filter.condition())
+        .order_by(sort.column, sort.order.into());
+
+    // Keep the order stable between pages when sorting on a column with duplicate values.
+    if !matches!(sort.column, movie::Column::Id) {
+        select = select.order_by_asc(movie::Column::Id);
+    }
+
+    paginate(db, select, movie::Column::Id, |movie| movie.id, pagination).await
+}
+
+#[instrument(skip_all)]
 pub async fn get_movie(db: &DbConn, id: i32) -> Result<Option<movie::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_movie");
+
     movie::Entity::find_by_id(id).one(db).await
 }
+
+#[instrument(skip_all)]
+pub async fn get_all_persons(
+    db: &DbConn,
+    pagination: Pagination,
+) -> Result<Page<person::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_all_persons");
+
+    let select = person::Entity::find().order_by_asc(person::Column::Id);
+
+    paginate(
+        db,
+        select,
+        person::Column::Id,
+        |person| person.id,
+        pagination,
+    )
+    .await
+}
+
+#[instrument(skip_all)]
+pub async fn get_person(db: &DbConn, id: i32) -> Result<Option<person::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_person");
+
+    person::Entity::find_by_id(id).one(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn get_all_genres(
+    db: &DbConn,
+    pagination: Pagination,
+) -> Result<Page<genre::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_all_genres");
+
+    let select = genre::Entity::find().order_by_asc(genre::Column::Id);
+
+    paginate(db, select, genre::Column::Id, |genre| genre.id, pagination).await
+}
+
+#[instrument(skip_all)]
+pub async fn get_genre(db: &DbConn, id: i32) -> Result<Option<genre::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_genre");
+
+    genre::Entity::find_by_id(id).one(db).await
+}
+
+#[instrument(skip_all)]
+pub async fn get_all_keywords(
+    db: &DbConn,
+    pagination: Pagination,
+) -> Result<Page<keyword::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_all_keywords");
+
+    let select = keyword::Entity::find().order_by_asc(keyword::Column::Id);
+
+    paginate(
+        db,
+        select,
+        keyword::Column::Id,
+        |keyword| keyword.id,
+        pagination,
+    )
+    .await
+}
+
+#[instrument(skip_all)]
+pub async fn get_keyword(db: &DbConn, id: i32) -> Result<Option<keyword::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_keyword");
+
+    keyword::Entity::find_by_id(id).one(db).await
+}
+
+/// A person credited on a movie.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MovieCredit {
+    pub credit_id: i32,
+    pub person_id: i32,
+    pub name: String,
+}
+
+/// The credits of a movie, grouped by type.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MovieCredits {
+    pub cast: Vec<MovieCredit>,
+    pub directors: Vec<MovieCredit>,
+    pub producers: Vec<MovieCredit>,
+}
+
+/// Get the credits of a movie, or `None` if there is no movie with this id.
+#[instrument(skip_all)]
+pub async fn get_movie_credits(db: &DbConn, movie_id: i32) -> Result<Option<MovieCredits>, DbErr> {
+    let _timer = QueryTimer::start("get_movie_credits");
+
+    let Some(movie) = movie::Entity::find_by_id(movie_id).one(db).await? else {
+        return Ok(None);
+    };
+
+    let credits = movie
+        .find_related(credit::Entity)
+        .find_also_related(person::Entity)
+        .order_by_asc(credit::Column::Id)
+        .all(db)
+        .await?;
+
+    let mut movie_credits = MovieCredits::default();
+
+    for (credit, person) in credits {
+        let Some(person) = person else {
+            continue;
+        };
+
+        let group = match credit.r#type {
+            CreditType::Actor => &mut movie_credits.cast,
+            CreditType::Director => &mut movie_credits.directors,
+            CreditType::Producer => &mut movie_credits.producers,
+        };
+
+        group.push(MovieCredit {
+            credit_id: credit.id,
+            person_id: person.id,
+            name: person.name,
+        });
+    }
+
+    Ok(Some(movie_credits))
+}
+
+/// A movie a person is credited on.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FilmographyEntry {
+    pub credit_id: i32,
+    pub r#type: CreditType,
+    pub movie: MovieResponse,
+}
+
+/// Get the movies a person is credited on, oldest first, or `None` if there is no person with
+/// this id.
+#[instrument(skip_all)]
+pub async fn get_person_filmography(
+    db: &DbConn,
+    person_id: i32,
+) -> Result<Option<Vec<FilmographyEntry>>, DbErr> {
+    let _timer = QueryTimer::start("get_person_filmography");
+
+    let Some(person) = person::Entity::find_by_id(person_id).one(db).await? else {
+        return Ok(None);
+    };
+
+    let credits = person
+        .find_related(credit::Entity)
+        .find_also_related(movie::Entity)
+        .order_by_asc(movie::Column::ReleaseDate)
+        .order_by_asc(credit::Column::Id)
+        .all(db)
+        .await?;
+
+    let filmography = credits
+        .into_iter()
+        .filter_map(|(credit, movie)| {
+            Some(FilmographyEntry {
+                credit_id: credit.id,
+                r#type: credit.r#type,
+                movie: movie?.into(),
+            })
+        })
+        .collect();
+
+    Ok(Some(filmography))
+}
+
+/// Related resources to embed in movies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MovieIncludes {
+    pub credits: bool,
+    /// Embed the credited person in each credit, only used along with `credits`
+    pub credit_persons: bool,
+    pub genres: bool,
+    pub keywords: bool,
+}
+
+/// A credit embedded in a movie.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct IncludedCredit {
+    pub id: i32,
+    pub person_id: i32,
+    pub r#type: CreditType,
+    /// Only present with `include=credits.person`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Person>)]
+    pub person: Option<person::Model>,
+}
+
+/// A movie with its requested related resources.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ExpandedMovie {
+    #[serde(flatten)]
+    pub movie: MovieResponse,
+    /// Only present with `include=credits`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credits: Option<Vec<IncludedCredit>>,
+    /// Only present with `include=genres`, sorted by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Genre>>)]
+    pub genres: Option<Vec<genre::Model>>,
+    /// Only present with `include=keywords`, sorted by name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Keyword>>)]
+    pub keywords: Option<Vec<keyword::Model>>,
+    /// Only present when getting a single movie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reviews: Option<MovieReviews>,
+}
+
+/// Number of reviews shown with a movie.
+pub const LATEST_REVIEWS: u64 = 3;
+
+/// The published reviews of a movie.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MovieReviews {
+    pub count: u64,
+    /// The most recent reviews, at most 3
+    #[schema(value_type = Vec<Review>)]
+    pub latest: Vec<review::Model>,
+}
+
+/// Load the related resources of `movies` selected by `includes`.
+///
+/// This runs one query per level of relation, however many movies there are.
+#[instrument(skip_all)]
+pub async fn load_movie_includes(
+    db: &DbConn,
+    movies: Vec<movie::Model>,
+    includes: MovieIncludes,
+) -> Result<Vec<ExpandedMovie>, DbErr> {
+    let _timer = QueryTimer::start("load_movie_includes");
+
+    let credits: Vec<_> = if includes.credits {
+        load_included_credits(db, &movies, includes.credit_persons)
+            .await?
+            .into_iter()
+            .map(Some)
+            .collect()
+    } else {
+        vec![None; movies.len()]
+    };
+
+    let genres: Vec<_> = if includes.genres {
+        movies
+            .load_many_to_many(genre::Entity, movie_genre::Entity, db)
+            .await?
+            .into_iter()
+            .map(|mut genres| {
+                genres.sort_by(|a, b| a.name.cmp(&b.name));
+                Some(genres)
+            })
+            .collect()
+    } else {
+        vec![None; movies.len()]
+    };
+
+    let keywords: Vec<_> = if includes.keywords {
+        movies
+            .load_many_to_many(keyword::Entity, movie_keyword::Entity, db)
+            .await?
+            .into_iter()
+            .map(|mut keywords| {
+                keywords.sort_by(|a, b| a.name.cmp(&b.name));
+                Some(keywords)
+            })
+            .collect()
+    } else {
+        vec![None; movies.len()]
+    };
+
+    let expanded = movies
+        .into_iter()
+        .zip(credits)
+        .zip(genres)
+        .zip(keywords)
+        .map(|(((movie, credits), genres), keywords)| ExpandedMovie {
+            movie: movie.into(),
+            credits,
+            genres,
+            keywords,
+            reviews: None,
+        })
+        .collect();
+
+    Ok(expanded)
+}
+
+async fn load_included_credits(
+    db: &DbConn,
+    movies: &[movie::Model],
+    with_persons: bool,
+) -> Result<Vec<Vec<IncludedCredit>>, DbErr> {
+    let credits = movies
+        .load_many(credit::Entity::find().order_by_asc(credit::Column::Id), db)
+        .await?;
+
+    let mut persons = if with_persons {
+        let all_credits: Vec<credit::Model> = credits.iter().flatten().cloned().collect();
+
+        all_credits.load_one(person::Entity, db).await?
+    } else {
+        vec![]
+    }
+    .into_iter();
+
+    let included = credits
+        .into_iter()
+        .map(|credits| {
+            credits
+                .into_iter()
+                .map(|credit| IncludedCredit {
+                    id: credit.id,
+                    person_id: credit.person_id,
+                    r#type: credit.r#type,
+                    person: persons.next().flatten(),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(included)
+}
+
+#[instrument(skip_all)]
+pub async fn get_all_api_keys(db: &DbConn) -> Result<Vec<api_key::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_all_api_keys");
+
+    api_key::Entity::find()
+        .order_by_asc(api_key::Column::Id)
+        .all(db)
+        .await
+}
+
+#[instrument(skip_all)]
+pub async fn get_all_users(db: &DbConn) -> Result<Vec<user::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_all_users");
+
+    user::Entity::find()
+        .order_by_asc(user::Column::Id)
+        .all(db)
+        .await
+}
+
+/// Get a page of the lists a user may see, which are the public ones and their own, or only the
+/// public ones for anonymous callers.
+#[instrument(skip_all)]
+pub async fn get_visible_lists(
+    db: &DbConn,
+    viewer_id: Option<i32>,
+    pagination: Pagination,
+) -> Result<Page<list::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_visible_lists");
+
+    let visible = Condition::any()
+        .add(list::Column::Visibility.eq(ListVisibility::Public))
+        .add_option(viewer_id.map(|viewer_id| list::Column::OwnerId.eq(viewer_id)));
+    let select = list::Entity::find()
+        .filter(visible)
+        .order_by_asc(list::Column::Id);
+
+    paginate(db, select, list::Column::Id, |list| list.id, pagination).await
+}
+
+#[instrument(skip_all)]
+pub async fn get_list(db: &DbConn, id: i32) -> Result<Option<list::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_list");
+
+    list::Entity::find_by_id(id).one(db).await
+}
+
+/// Get the items of a list in order, along with a summary of their movie.
+#[instrument(skip_all)]
+pub async fn get_list_items(db: &DbConn, list_id: i32) -> Result<Vec<ListItemResponse>, DbErr> {
+    let _timer = QueryTimer::start("get_list_items");
+
+    let items = list_item::Entity::find()
+        .filter(list_item::Column::ListId.eq(list_id))
+        .order_by_asc(list_item::Column::Position)
+        .find_also_related(movie::Entity)
+        .all(db)
+        .await?;
+
+    Ok(items
+        .into_iter()
+        .filter_map(|(item, movie)| {
+            Some(ListItemResponse {
+                id: item.id,
+                position: item.position,
+                note: item.note,
+                added_at: item.added_at,
+                movie: movie?.into(),
+            })
+        })
+        .collect())
+}
+
+/// Get the ratings a user gave, most recent first.
+#[instrument(skip_all)]
+pub async fn get_user_ratings(db: &DbConn, user_id: i32) -> Result<Vec<user_rating::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_user_ratings");
+
+    user_rating::Entity::find()
+        .filter(user_rating::Column::UserId.eq(user_id))
+        .order_by_desc(user_rating::Column::RatedAt)
+        .order_by_desc(user_rating::Column::MovieId)
+        .all(db)
+        .await
+}
+
+/// Find the API key matching `key`, unless it was revoked.
+#[instrument(skip_all)]
+pub async fn find_api_key(db: &DbConn, key: &str) -> Result<Option<api_key::Model>, DbErr> {
+    let _timer = QueryTimer::start("find_api_key");
+
+    api_key::Entity::find()
+        .filter(api_key::Column::KeyHash.eq(hash_token(key)))
+        .filter(api_key::Column::RevokedAt.is_null())
+        .one(db)
+        .await
+}
+
+#[instrument(skip_all)]
+pub async fn find_user_by_email(db: &DbConn, email: &str) -> Result<Option<user::Model>, DbErr> {
+    let _timer = QueryTimer::start("find_user_by_email");
+
+    user::Entity::find()
+        .filter(user::Column::Email.eq(normalize_email(email)))
+        .one(db)
+        .await
+}
+
+/// Find the user logged in with the session `token`, unless it expired.
+#[instrument(skip_all)]
+pub async fn find_session_user(db: &DbConn, token: &str) -> Result<Option<user::Model>, DbErr> {
+    let _timer = QueryTimer::start("find_session_user");
+
+    user::Entity::find()
+        .inner_join(session::Entity)
+        .filter(session::Column::TokenHash.eq(hash_token(token)))
+        .filter(session::Column::ExpiresAt.gt(chrono::Utc::now()))
+        .one(db)
+        .await
+}
+
+#[instrument(skip_all)]
+pub async fn get_review(db: &DbConn, id: i32) -> Result<Option<review::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_review");
+
+    review::Entity::find_by_id(id).one(db).await
+}
+
+/// Get a page of the published reviews, of every movie or only of the given one.
+#[instrument(skip_all)]
+pub async fn get_published_reviews(
+    db: &DbConn,
+    movie_id: Option<i32>,
+    pagination: Pagination,
+) -> Result<Page<review::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_published_reviews");
+
+    let select = review::Entity::find()
+        .filter(review::Column::Status.eq(ReviewStatus::Published))
+        .apply_if(movie_id, |select, movie_id| {
+            select.filter(review::Column::MovieId.eq(movie_id))
+        })
+        .order_by_asc(review::Column::Id);
+
+    paginate(
+        db,
+        select,
+        review::Column::Id,
+        |review| review.id,
+        pagination,
+    )
+    .await
+}
+
+/// Get a page of the reviews waiting for a moderator, oldest first.
+#[instrument(skip_all)]
+pub async fn get_pending_reviews(
+    db: &DbConn,
+    pagination: Pagination,
+) -> Result<Page<review::Model>, DbErr> {
+    let _timer = QueryTimer::start("get_pending_reviews");
+
+    let select = review::Entity::find()
+        .filter(review::Column::Status.eq(ReviewStatus::Pending))
+        .order_by_asc(review::Column::Id);
+
+    paginate(
+        db,
+        select,
+        review::Column::Id,
+        |review| review.id,
+        pagination,
+    )
+    .await
+}
+
+/// Count the published reviews of a movie and get the most recent ones.
+#[instrument(skip_all)]
+pub async fn get_movie_reviews(db: &DbConn, movie_id: i32) -> Result<MovieReviews, DbErr> {
+    let _timer = QueryTimer::start("get_movie_reviews");
+
+    let published = review::Entity::find()
+        .filter(review::Column::MovieId.eq(movie_id))
+        .filter(review::Column::Status.eq(ReviewStatus::Published));
+
+    let count = published.clone().count(db).await?;
+    let latest = published
+        .order_by_desc(review::Column::CreatedAt)
+        .order_by_desc(review::Column::Id)
+        .limit(LATEST_REVIEWS)
+        .all(db)
+        .await?;
+
+    Ok(MovieReviews { count, latest })
+}
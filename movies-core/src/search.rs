@@ -0,0 +1,247 @@
+use ::movies_entity::{movie, person};
+use chrono::Datelike;
+use sea_orm::sea_query::{Expr, Func, IntoColumnRef, LikeExpr, SimpleExpr};
+use sea_orm::*;
+use serde::Serialize;
+use tracing::instrument;
+use utoipa::ToSchema;
+
+use crate::query::escape_like;
+use crate::timing::QueryTimer;
+
+/// Delimiters PostgreSQL puts around the matched terms of snippets, from the Unicode private use
+/// area so that they cannot clash with the text. They are replaced by `<mark>` tags once the text
+/// is escaped.
+const START_SEL: char = '\u{E000}';
+const STOP_SEL: char = '\u{E001}';
+
+/// Options of `ts_headline` for the snippets of hits.
+const HEADLINE_OPTIONS: &str =
+    "StartSel=\u{E000}, StopSel=\u{E001}, MinWords=10, MaxWords=30, MaxFragments=2, \
+     FragmentDelimiter=\" … \"";
+
+/// Number of characters kept around the match in the snippets of the LIKE search.
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchHitType {
+    Movie,
+    Person,
+}
+
+/// A movie or person matching a search.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SearchHit {
+    pub r#type: SearchHitType,
+    pub id: i32,
+    /// Title of the movie or name of the person
+    pub label: String,
+    /// Year the movie was released, absent for persons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    /// Excerpt of the matching text as HTML, with the matched terms in `<mark>` tags
+    pub snippet: String,
+    /// Relevance of the hit, the higher the better
+    pub rank: f64,
+}
+
+/// Search movie titles and descriptions and person names, best hits first.
+///
+/// PostgreSQL matches the words of the query, stemmed, against the `search_vector` columns, with
+/// the web search syntax of `websearch_to_tsquery`. Other databases match the whole query as a
+/// substring, ignoring ASCII case.
+#[instrument(skip_all)]
+pub async fn search(db: &DbConn, query: &str, limit: u64) -> Result<Vec<SearchHit>, DbErr> {
+    let _timer = QueryTimer::start("search");
+
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut hits = match db.get_database_backend() {
+        DbBackend::Postgres => search_full_text(db, query, limit).await?,
+        _ => search_like(db, query, limit).await?,
+    };
+
+    // Stable, so that movies stay before persons with the same rank
+    hits.sort_by(|a, b| b.rank.total_cmp(&a.rank));
+    hits.truncate(limit as usize);
+
+    Ok(hits)
+}
+
+#[derive(FromQueryResult)]
+struct FullTextHit {
+    id: i32,
+    label: String,
+    year: Option<i32>,
+    snippet: String,
+    rank: f64,
+}
+
+async fn search_full_text(db: &DbConn, query: &str, limit: u64) -> Result<Vec<SearchHit>, DbErr> {
+    let movies = FullTextHit::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT id, title AS label,
+                  CAST(EXTRACT(YEAR FROM release_date) AS integer) AS year,
+                  ts_headline(
+                      'english',
+                      CASE WHEN to_tsvector('english', description) @@ query
+                           THEN description ELSE title END,
+                      query,
+                      $3
+                  ) AS snippet,
+                  CAST(ts_rank(search_vector, query) AS double precision) AS rank
+           FROM movie, websearch_to_tsquery('english', $1) AS query
+           WHERE search_vector @@ query
+           ORDER BY rank DESC, id
+           LIMIT $2"#,
+        [query.into(), limit.into(), HEADLINE_OPTIONS.into()],
+    ))
+    .all(db)
+    .await?;
+
+    let persons = FullTextHit::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT id, name AS label, CAST(NULL AS integer) AS year,
+                  ts_headline('simple', name, query, $3) AS snippet,
+                  CAST(ts_rank(search_vector, query) AS double precision) AS rank
+           FROM person, websearch_to_tsquery('simple', $1) AS query
+           WHERE search_vector @@ query
+           ORDER BY rank DESC, id
+           LIMIT $2"#,
+        [query.into(), limit.into(), HEADLINE_OPTIONS.into()],
+    ))
+    .all(db)
+    .await?;
+
+    let typed = |r#type| {
+        move |hit: FullTextHit| SearchHit {
+            r#type,
+            id: hit.id,
+            label: hit.label,
+            year: hit.year,
+            snippet: escape_html(&hit.snippet)
+                .replace(START_SEL, "<mark>")
+                .replace(STOP_SEL, "</mark>"),
+            rank: hit.rank,
+        }
+    };
+
+    Ok(movies
+        .into_iter()
+        .map(typed(SearchHitType::Movie))
+        .chain(persons.into_iter().map(typed(SearchHitType::Person)))
+        .collect())
+}
+
+async fn search_like(db: &DbConn, query: &str, limit: u64) -> Result<Vec<SearchHit>, DbErr> {
+    let pattern = format!("%{}%", escape_like(&query.to_ascii_lowercase()));
+    let contains = |column| lower_like(column, &pattern);
+
+    let movies = movie::Entity::find()
+        .filter(
+            Condition::any()
+                .add(contains(movie::Column::Title))
+                .add(contains(movie::Column::Description)),
+        )
+        .order_by_asc(movie::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    let persons = person::Entity::find()
+        .filter(lower_like(person::Column::Name, &pattern))
+        .order_by_asc(person::Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    let movie_hits = movies.into_iter().map(|movie| {
+        // Matches in titles matter more, as they do with the weights of the search vector
+        let (snippet, rank) = match highlight(&movie.title, query) {
+            Some(snippet) => (snippet, 1.0),
+            None => (
+                highlight(&movie.description, query).unwrap_or_default(),
+                0.5,
+            ),
+        };
+
+        SearchHit {
+            r#type: SearchHitType::Movie,
+            id: movie.id,
+            year: Some(movie.release_date.year()),
+            label: movie.title,
+            snippet,
+            rank,
+        }
+    });
+
+    let person_hits = persons.into_iter().map(|person| SearchHit {
+        r#type: SearchHitType::Person,
+        id: person.id,
+        snippet: highlight(&person.name, query).unwrap_or_default(),
+        label: person.name,
+        year: None,
+        rank: 1.0,
+    });
+
+    Ok(movie_hits.chain(person_hits).collect())
+}
+
+fn lower_like(column: impl IntoColumnRef, pattern: &str) -> SimpleExpr {
+    Expr::expr(Func::lower(Expr::col(column))).like(LikeExpr::new(pattern).escape('\\'))
+}
+
+/// Escape `text` and wrap the first match of `query` in a `<mark>` tag, keeping some context
+/// around it, or `None` if it does not match.
+fn highlight(text: &str, query: &str) -> Option<String> {
+    // ASCII lowercasing keeps byte offsets, and is what LOWER() does on SQLite
+    let start = text
+        .to_ascii_lowercase()
+        .find(&query.to_ascii_lowercase())?;
+    let end = start + query.len();
+    let (before, after) = (&text[..start], &text[end..]);
+
+    let context_start = before
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT - 1)
+        .map_or(0, |(index, _)| index);
+    let context_end = after
+        .char_indices()
+        .nth(SNIPPET_CONTEXT)
+        .map_or(after.len(), |(index, _)| index);
+
+    Some(format!(
+        "{}{}<mark>{}</mark>{}{}",
+        if context_start > 0 { "… " } else { "" },
+        escape_html(&before[context_start..]),
+        escape_html(&text[start..end]),
+        escape_html(&after[..context_end]),
+        if context_end < after.len() {
+            " …"
+        } else {
+            ""
+        },
+    ))
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+/// Histogram of how long the functions of this crate take, labelled by function name.
+pub const QUERY_DURATION_METRIC: &str = "movies_core_query_duration_seconds";
+
+/// Records the time elapsed since its creation in `QUERY_DURATION_METRIC` when dropped, so that
+/// early returns and errors are measured too.
+pub(crate) struct QueryTimer {
+    function: &'static str,
+    start: Instant,
+}
+
+impl QueryTimer {
+    pub(crate) fn start(function: &'static str) -> Self {
+        QueryTimer {
+            function,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        metrics::histogram!(QUERY_DURATION_METRIC, "function" => self.function)
+            .record(self.start.elapsed().as_secs_f64());
+    }
+}
@@ -0,0 +1,37 @@
+use rand::distributions::{Alphanumeric, DistString};
+use sha2::{Digest, Sha256};
+
+/// Start of every API key, telling them apart from other secrets.
+const API_KEY_PREFIX: &str = "mvk_";
+/// Random characters following `API_KEY_PREFIX` that identify a key, stored in clear.
+const API_KEY_ID_LEN: usize = 8;
+/// Random characters making up the secret part of a key, or a whole session token.
+const SECRET_LEN: usize = 32;
+
+/// Generate a new API key, returning it with its prefix identifying it.
+pub(crate) fn generate_api_key() -> (String, String) {
+    let mut rng = rand::thread_rng();
+    let prefix = format!(
+        "{API_KEY_PREFIX}{}",
+        Alphanumeric.sample_string(&mut rng, API_KEY_ID_LEN)
+    );
+    let key = format!(
+        "{prefix}_{}",
+        Alphanumeric.sample_string(&mut rng, SECRET_LEN)
+    );
+
+    (key, prefix)
+}
+
+/// Generate the token identifying a new session.
+pub(crate) fn generate_session_token() -> String {
+    Alphanumeric.sample_string(&mut rand::thread_rng(), SECRET_LEN)
+}
+
+/// Hash of an API key or session token as stored in the database.
+///
+/// The tokens are long random strings, so a fast hash without salt is enough to make the stored
+/// hashes useless to whoever reads them, while allowing to look tokens up by hash.
+pub(crate) fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,449 @@
+use ::movies_entity::person;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::dto::{
+    AddListItem, CreateGenre, CreateKeyword, CreateList, CreateMovie, CreateReview, PartialList,
+    PartialMovie, PartialReview, RateMovie, RegisterUser, ReplaceMovie,
+};
+use crate::mutation::PartialPerson;
+use crate::query::{MovieFilter, TaxonomyFilter};
+
+/// Lowest score any rating scale may start at, as spelled out by the CHECK constraints on the
+/// `movie` and `user_rating` tables.
+pub const MIN_RATING: i32 = 0;
+/// Highest score any rating scale may go up to, see [`MIN_RATING`].
+pub const MAX_RATING: i32 = 10;
+
+/// Scores accepted for the ratings of editors and users, configured within [`MIN_RATING`] and
+/// [`MAX_RATING`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RatingScale {
+    min: i32,
+    max: i32,
+}
+
+impl Default for RatingScale {
+    fn default() -> Self {
+        RatingScale {
+            min: MIN_RATING,
+            max: MAX_RATING,
+        }
+    }
+}
+
+impl RatingScale {
+    /// Scale from `min` to `max` included, which must fit within the bounds of the database.
+    pub fn new(min: i32, max: i32) -> Result<Self, String> {
+        if min < MIN_RATING || max > MAX_RATING {
+            Err(format!("must be between {MIN_RATING} and {MAX_RATING}"))
+        } else if min >= max {
+            Err("must have its lowest score below its highest".into())
+        } else {
+            Ok(RatingScale { min, max })
+        }
+    }
+
+    pub fn min(&self) -> i32 {
+        self.min
+    }
+
+    pub fn max(&self) -> i32 {
+        self.max
+    }
+
+    fn check(&self, rating: i32) -> Result<(), String> {
+        if (self.min..=self.max).contains(&rating) {
+            Ok(())
+        } else {
+            Err(format!("must be between {} and {}", self.min, self.max))
+        }
+    }
+}
+
+/// Shortest password accepted for new accounts.
+pub const MIN_PASSWORD_LEN: usize = 8;
+/// Longest password accepted for new accounts, bounding the time spent hashing it.
+pub const MAX_PASSWORD_LEN: usize = 128;
+
+/// Longest markdown body accepted for reviews, in characters.
+pub const MAX_REVIEW_BODY_LEN: usize = 20_000;
+
+/// Earliest accepted release date, a little before the first motion pictures.
+pub fn earliest_release_date() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(1870, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Release dates must be strictly before this one.
+pub fn latest_release_date() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Why the value of a field was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Input whose fields can be checked before being written to the database.
+pub trait Validate {
+    /// Check every field, returning all the errors found rather than stopping at the first one.
+    fn validate(&self) -> Result<(), Vec<FieldError>>;
+}
+
+/// Input holding ratings, whose bounds depend on the configured scale.
+pub trait ValidateOnScale {
+    /// Check every field like [`Validate::validate`], ratings against `scale`.
+    fn validate_on(&self, scale: &RatingScale) -> Result<(), Vec<FieldError>>;
+}
+
+#[derive(Default)]
+struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    fn check(&mut self, field: &str, result: Result<(), String>) {
+        if let Err(message) = result {
+            self.0.push(FieldError {
+                field: field.to_owned(),
+                message,
+            });
+        }
+    }
+
+    fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+fn check_not_blank(value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        Err("must not be empty".into())
+    } else {
+        Ok(())
+    }
+}
+
+fn check_review_body(body: &str) -> Result<(), String> {
+    check_not_blank(body)?;
+
+    if body.chars().count() > MAX_REVIEW_BODY_LEN {
+        return Err(format!(
+            "must be at most {MAX_REVIEW_BODY_LEN} characters long"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Ids are generated by `serial` columns, which start at 1.
+fn check_id(id: i32) -> Result<(), String> {
+    if id > 0 {
+        Ok(())
+    } else {
+        Err("must be a positive id".into())
+    }
+}
+
+fn check_taxonomy_filter(filter: &TaxonomyFilter) -> Result<(), String> {
+    if filter.ids.iter().all(|&id| id > 0) {
+        Ok(())
+    } else {
+        Err("must only contain positive ids".into())
+    }
+}
+
+fn check_position(position: i32) -> Result<(), String> {
+    if position >= 0 {
+        Ok(())
+    } else {
+        Err("must not be negative".into())
+    }
+}
+
+/// An empty poster URL means the movie has no poster, otherwise it must be an absolute HTTP(S)
+/// URL.
+///
+/// The URL is stored as given, so it is checked as written rather than as `Url::parse` reads it,
+/// which forgives surrounding whitespace and missing slashes: the `movie_poster_url_absolute`
+/// constraint would reject those.
+fn check_poster_url(poster_url: &str) -> Result<(), String> {
+    if poster_url.is_empty() {
+        return Ok(());
+    }
+
+    let authority = ["http://", "https://"].into_iter().find_map(|scheme| {
+        let prefix = poster_url.get(..scheme.len())?;
+        prefix
+            .eq_ignore_ascii_case(scheme)
+            .then(|| &poster_url[scheme.len()..])
+    });
+    let has_host = authority.is_some_and(|authority| {
+        authority
+            .chars()
+            .next()
+            .is_some_and(|c| c != '/' && !c.is_whitespace())
+    });
+
+    match Url::parse(poster_url) {
+        Ok(url) if has_host && url.has_host() && !poster_url.contains(char::is_whitespace) => {
+            Ok(())
+        }
+        _ => Err("must be an absolute http or https URL".into()),
+    }
+}
+
+/// Only checks the rough shape of an address, whether it exists is up to its owner.
+fn check_email(email: &str) -> Result<(), String> {
+    match email.trim().split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => Ok(()),
+        _ => Err("must be an email address".into()),
+    }
+}
+
+fn check_password(password: &str) -> Result<(), String> {
+    if (MIN_PASSWORD_LEN..=MAX_PASSWORD_LEN).contains(&password.chars().count()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "must be between {MIN_PASSWORD_LEN} and {MAX_PASSWORD_LEN} characters long"
+        ))
+    }
+}
+
+fn check_release_date(release_date: DateTime<Utc>) -> Result<(), String> {
+    if (earliest_release_date()..latest_release_date()).contains(&release_date) {
+        Ok(())
+    } else {
+        Err(format!(
+            "must be between {} and {}",
+            earliest_release_date().format("%Y-%m-%d"),
+            latest_release_date().format("%Y-%m-%d")
+        ))
+    }
+}
+
+/// Check the ids of resources taken from the path of a request, each named after its parameter.
+pub fn validate_ids(ids: &[(&str, i32)]) -> Result<(), Vec<FieldError>> {
+    let mut errors = FieldErrors::default();
+
+    for &(field, id) in ids {
+        errors.check(field, check_id(id));
+    }
+
+    errors.finish()
+}
+
+impl ValidateOnScale for MovieFilter {
+    fn validate_on(&self, scale: &RatingScale) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        if let Some(min_rating) = self.min_rating {
+            errors.check("min_rating", scale.check(min_rating));
+        }
+        if let Some(max_rating) = self.max_rating {
+            errors.check("max_rating", scale.check(max_rating));
+        }
+        if let (Some(min), Some(max)) = (self.min_rating, self.max_rating) {
+            if min > max {
+                errors.check(
+                    "max_rating",
+                    Err("must not be less than `min_rating`".into()),
+                );
+            }
+        }
+        if let (Some(after), Some(before)) = (self.released_after, self.released_before) {
+            if after > before {
+                errors.check(
+                    "released_before",
+                    Err("must not be earlier than `released_after`".into()),
+                );
+            }
+        }
+        if let Some(genres) = &self.genres {
+            errors.check("genre", check_taxonomy_filter(genres));
+        }
+        if let Some(keywords) = &self.keywords {
+            errors.check("keyword", check_taxonomy_filter(keywords));
+        }
+
+        errors.finish()
+    }
+}
+
+impl ValidateOnScale for CreateMovie {
+    fn validate_on(&self, scale: &RatingScale) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("title", check_not_blank(&self.title));
+        errors.check("release_date", check_release_date(self.release_date));
+        errors.check("poster_url", check_poster_url(&self.poster_url));
+        errors.check("rating", scale.check(self.rating));
+
+        errors.finish()
+    }
+}
+
+impl ValidateOnScale for ReplaceMovie {
+    fn validate_on(&self, scale: &RatingScale) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("title", check_not_blank(&self.title));
+        errors.check("release_date", check_release_date(self.release_date));
+        errors.check("poster_url", check_poster_url(&self.poster_url));
+        errors.check("rating", scale.check(self.rating));
+
+        errors.finish()
+    }
+}
+
+impl ValidateOnScale for PartialMovie {
+    fn validate_on(&self, scale: &RatingScale) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        if let Some(title) = &self.title {
+            errors.check("title", check_not_blank(title));
+        }
+        if let Some(release_date) = self.release_date {
+            errors.check("release_date", check_release_date(release_date));
+        }
+        if let Some(poster_url) = &self.poster_url {
+            errors.check("poster_url", check_poster_url(poster_url));
+        }
+        if let Some(rating) = self.rating {
+            errors.check("rating", scale.check(rating));
+        }
+
+        errors.finish()
+    }
+}
+
+impl ValidateOnScale for RateMovie {
+    fn validate_on(&self, scale: &RatingScale) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("score", scale.check(self.score));
+
+        errors.finish()
+    }
+}
+
+impl Validate for CreateList {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("name", check_not_blank(&self.name));
+
+        errors.finish()
+    }
+}
+
+impl Validate for PartialList {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        if let Some(name) = &self.name {
+            errors.check("name", check_not_blank(name));
+        }
+
+        errors.finish()
+    }
+}
+
+impl Validate for AddListItem {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        if let Some(position) = self.position {
+            errors.check("position", check_position(position));
+        }
+
+        errors.finish()
+    }
+}
+
+impl Validate for CreateReview {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("title", check_not_blank(&self.title));
+        errors.check("body", check_review_body(&self.body));
+
+        errors.finish()
+    }
+}
+
+impl Validate for PartialReview {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        if let Some(title) = &self.title {
+            errors.check("title", check_not_blank(title));
+        }
+        if let Some(body) = &self.body {
+            errors.check("body", check_review_body(body));
+        }
+
+        errors.finish()
+    }
+}
+
+impl Validate for CreateGenre {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("name", check_not_blank(&self.name));
+
+        errors.finish()
+    }
+}
+
+impl Validate for CreateKeyword {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("name", check_not_blank(&self.name));
+
+        errors.finish()
+    }
+}
+
+impl Validate for person::Model {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("name", check_not_blank(&self.name));
+
+        errors.finish()
+    }
+}
+
+impl Validate for PartialPerson {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        if let Some(name) = &self.name {
+            errors.check("name", check_not_blank(name));
+        }
+
+        errors.finish()
+    }
+}
+
+impl Validate for RegisterUser {
+    fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::default();
+
+        errors.check("email", check_email(&self.email));
+        errors.check("display_name", check_not_blank(&self.display_name));
+        errors.check("password", check_password(&self.password));
+
+        errors.finish()
+    }
+}
@@ -1,13 +1,39 @@
 use chrono::{TimeZone, Utc};
-use movies_core::{create_movie, get_all_movies};
-use movies_entity::movie::Model;
+use movies_core::{
+    add_list_item, assign_genre, assign_keyword, create_api_key, create_credit, create_genre,
+    create_keyword, create_list, create_movie, create_person, create_review, create_session,
+    create_user, delete_movie, delete_person, delete_session, delete_user, find_api_key,
+    find_session_user, find_user_by_email, get_all_movies, get_all_persons, get_all_users,
+    get_list_items, get_movie, get_movie_credits, get_movie_reviews, get_pending_reviews,
+    get_person_filmography, get_published_reviews, get_user_ratings, get_visible_lists,
+    hash_password, load_movie_includes, moderate_review, rate_movie, remove_list_item,
+    reorder_list, revoke_api_key, search, unassign_genre, unrate_movie, update_movie,
+    update_movie_partial, update_person_partial, update_review, update_user_role, upsert_oidc_user,
+    validate_ids, verify_password, AddListItem, Change, ChangeListener, CreateGenre, CreateKeyword,
+    CreateList, CreateMovie, CreateReview, FieldError, ListItemResponse, MovieCredit, MovieFilter,
+    MovieIncludes, MovieResponse, MovieSort, OidcIdentity, Pagination, PartialMovie, PartialPerson,
+    PartialReview, RateMovie, RatingScale, RatingSummary, ReorderList, ReplaceMovie, SearchHitType,
+    SortOrder, TaxonomyFilter, TaxonomyMatch, ValidateOnScale,
+};
+use movies_entity::movie::{Column, Model};
+use movies_entity::sea_orm_active_enums::{
+    ApiKeyScope, CreditType, ListVisibility, ReviewStatus, UserRole,
+};
+use movies_entity::{credit, person};
 use sea_orm::DbErr;
 use setup::prepare_test_db;
+use std::sync::Mutex;
 
 mod setup;
 
-fn assert_eq_ignore_id(mut this: Model, that: Model) {
-    this.id = that.id;
+fn assert_eq_ignore_id(this: Model, that: CreateMovie) {
+    let this = CreateMovie {
+        title: this.title,
+        release_date: this.release_date,
+        poster_url: this.poster_url,
+        description: this.description,
+        rating: this.rating,
+    };
 
     assert_eq!(this, that);
 }
@@ -17,8 +43,7 @@ async fn create_and_list_movies() -> Result<(), DbErr> {
     // arrange
     let db = prepare_test_db().await?;
 
-    let star_wars = Model {
-        id: 0,
+    let star_wars = CreateMovie {
         title: "Star Wars: Episode IV - A New Hope".to_owned(),
         release_date: Utc.with_ymd_and_hms(1977, 10, 19, 0, 0, 0).unwrap(),
         poster_url: Default::default(),
@@ -26,8 +51,7 @@ async fn create_and_list_movies() -> Result<(), DbErr> {
         rating: 5,
     };
 
-    let dune = Model {
-        id: 0,
+    let dune = CreateMovie {
         title: "Dune".to_owned(),
         release_date: Utc.with_ymd_and_hms(1984, 12, 3, 0, 0, 0).unwrap(),
         poster_url: Default::default(),
@@ -36,15 +60,1238 @@ async fn create_and_list_movies() -> Result<(), DbErr> {
     };
 
     // act
-    create_movie(&db, star_wars.clone()).await?;
-    create_movie(&db, dune.clone()).await?;
+    create_movie(&db, &(), star_wars.clone()).await?;
+    create_movie(&db, &(), dune.clone()).await?;
 
-    let movies = get_all_movies(&db).await?;
+    let page = get_all_movies(
+        &db,
+        &MovieFilter::default(),
+        MovieSort::default(),
+        Pagination::default(),
+    )
+    .await?;
 
     // assert
-    for (this, that) in movies.into_iter().zip([star_wars, dune].into_iter()) {
+    assert_eq!(page.total, 2);
+
+    for (this, that) in page.items.into_iter().zip([star_wars, dune].into_iter()) {
         assert_eq_ignore_id(this, that);
     }
 
     Ok(())
 }
+
+fn movie_titled(title: &str) -> CreateMovie {
+    CreateMovie {
+        title: title.to_owned(),
+        release_date: Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(),
+        poster_url: Default::default(),
+        description: Default::default(),
+        rating: 3,
+    }
+}
+
+#[tokio::test]
+async fn replace_and_patch_movie() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    let movie = create_movie(&db, &(), movie_titled("Alien")).await?;
+
+    let replacement = ReplaceMovie {
+        title: "Aliens".to_owned(),
+        release_date: Utc.with_ymd_and_hms(1986, 7, 18, 0, 0, 0).unwrap(),
+        poster_url: "https://example.com/aliens.jpg".to_owned(),
+        description: "This time it's war.".to_owned(),
+        rating: 4,
+    };
+
+    // act
+    let replaced = update_movie(&db, &(), movie.id, replacement.clone()).await?;
+    let patched = update_movie_partial(
+        &db,
+        &(),
+        movie.id,
+        PartialMovie {
+            rating: Some(5),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let missing = update_movie(&db, &(), -1, replacement).await;
+
+    // assert
+    assert_eq!(replaced.id, movie.id);
+    assert_eq!(replaced.title, "Aliens");
+    assert_eq!(patched.title, "Aliens");
+    assert_eq!(patched.rating, 5);
+    assert!(matches!(missing, Err(DbErr::RecordNotFound(_))));
+
+    Ok(())
+}
+
+/// Keeps every change it is told about.
+#[derive(Default)]
+struct RecordedChanges(Mutex<Vec<Change>>);
+
+impl ChangeListener for RecordedChanges {
+    fn changed(&self, change: Change) {
+        self.0.lock().unwrap().push(change);
+    }
+}
+
+#[tokio::test]
+async fn tell_listeners_about_saved_and_deleted_movies_and_persons() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let changes = RecordedChanges::default();
+    let user = create_user(
+        &db,
+        "jane@example.com",
+        "Jane".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+
+    // act
+    let movie = create_movie(&db, &changes, movie_titled("Alien")).await?;
+    let patched = update_movie_partial(
+        &db,
+        &changes,
+        movie.id,
+        PartialMovie {
+            title: Some("Aliens".to_owned()),
+            ..Default::default()
+        },
+    )
+    .await?;
+    rate_movie(&db, user.id, movie.id, 8).await?;
+    let missing = update_movie_partial(&db, &changes, movie.id + 1, PartialMovie::default()).await;
+    delete_movie(&db, &changes, movie.id).await?;
+    delete_movie(&db, &changes, movie.id).await?;
+
+    let person = create_person(
+        &db,
+        &changes,
+        person::Model {
+            id: 0,
+            name: "Ridley Scott".to_owned(),
+        },
+    )
+    .await?;
+    delete_person(&db, &changes, person.id).await?;
+
+    // assert
+    assert!(matches!(missing, Err(DbErr::RecordNotFound(_))));
+    assert_eq!(
+        changes.0.into_inner().unwrap(),
+        [
+            Change::MovieSaved(movie.clone()),
+            Change::MovieSaved(patched),
+            Change::MovieDeleted(movie.id),
+            Change::PersonSaved(person.clone()),
+            Change::PersonDeleted(person.id),
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn paginate_movies_with_offset() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    for i in 0..5 {
+        create_movie(&db, &(), movie_titled(&format!("Movie {i}"))).await?;
+    }
+
+    // act
+    let page = get_all_movies(
+        &db,
+        &MovieFilter::default(),
+        MovieSort::default(),
+        Pagination::Offset {
+            limit: 2,
+            offset: 2,
+        },
+    )
+    .await?;
+
+    // assert
+    let titles: Vec<_> = page
+        .items
+        .iter()
+        .map(|movie| movie.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Movie 2", "Movie 3"]);
+    assert_eq!(page.total, 5);
+    assert_eq!(page.page_size, 2);
+    assert_eq!(
+        page.next,
+        Some(Pagination::Offset {
+            limit: 2,
+            offset: 4
+        })
+    );
+    assert_eq!(
+        page.prev,
+        Some(Pagination::Offset {
+            limit: 2,
+            offset: 0
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn paginate_movies_with_cursor() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    let mut ids = vec![];
+    for i in 0..5 {
+        ids.push(
+            create_movie(&db, &(), movie_titled(&format!("Movie {i}")))
+                .await?
+                .id,
+        );
+    }
+
+    // act
+    let first = get_all_movies(
+        &db,
+        &MovieFilter::default(),
+        MovieSort::default(),
+        Pagination::After { limit: 2, id: 0 },
+    )
+    .await?;
+    let second = get_all_movies(
+        &db,
+        &MovieFilter::default(),
+        MovieSort::default(),
+        first.next.unwrap(),
+    )
+    .await?;
+    let back = get_all_movies(
+        &db,
+        &MovieFilter::default(),
+        MovieSort::default(),
+        second.prev.unwrap(),
+    )
+    .await?;
+    let last = get_all_movies(
+        &db,
+        &MovieFilter::default(),
+        MovieSort::default(),
+        Pagination::After {
+            limit: 2,
+            id: ids[3],
+        },
+    )
+    .await?;
+
+    // assert
+    let ids_of = |page: &movies_core::Page<Model>| -> Vec<i32> {
+        page.items.iter().map(|movie| movie.id).collect()
+    };
+
+    assert_eq!(ids_of(&first), ids[0..2]);
+    assert_eq!(first.prev, None);
+    assert_eq!(ids_of(&second), ids[2..4]);
+    assert_eq!(ids_of(&back), ids[0..2]);
+    assert_eq!(back.prev, None);
+    assert_eq!(ids_of(&last), ids[4..5]);
+    assert_eq!(last.next, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn filter_and_sort_movies() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    for (title, year, rating) in [
+        ("Alien", 1979, 5),
+        ("Aliens", 1986, 4),
+        ("Alien 3", 1992, 2),
+        ("Blade Runner", 1982, 5),
+    ] {
+        create_movie(
+            &db,
+            &(),
+            CreateMovie {
+                release_date: Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap(),
+                rating,
+                ..movie_titled(title)
+            },
+        )
+        .await?;
+    }
+
+    let filter = MovieFilter {
+        released_after: Some(Utc.with_ymd_and_hms(1980, 1, 1, 0, 0, 0).unwrap()),
+        min_rating: Some(3),
+        title: Some("ALIEN".to_owned()),
+        ..Default::default()
+    };
+    let sort = MovieSort {
+        column: Column::Rating,
+        order: SortOrder::Desc,
+    };
+
+    // act
+    let filtered =
+        get_all_movies(&db, &filter, MovieSort::default(), Pagination::default()).await?;
+    let sorted = get_all_movies(&db, &MovieFilter::default(), sort, Pagination::default()).await?;
+
+    // assert
+    let titles: Vec<_> = filtered
+        .items
+        .iter()
+        .map(|movie| movie.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Aliens"]);
+    assert_eq!(filtered.total, 1);
+
+    let titles: Vec<_> = sorted
+        .items
+        .iter()
+        .map(|movie| movie.title.as_str())
+        .collect();
+    assert_eq!(titles, ["Alien", "Blade Runner", "Aliens", "Alien 3"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_update_and_list_persons() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    let person = create_person(
+        &db,
+        &(),
+        person::Model {
+            id: 0,
+            name: "Ridley Scot".to_owned(),
+        },
+    )
+    .await?;
+
+    // act
+    update_person_partial(
+        &db,
+        &(),
+        person.id,
+        PartialPerson {
+            name: Some("Ridley Scott".to_owned()),
+        },
+    )
+    .await?;
+
+    let page = get_all_persons(&db, Pagination::default()).await?;
+
+    // assert
+    assert_eq!(
+        page.items,
+        [person::Model {
+            id: person.id,
+            name: "Ridley Scott".to_owned(),
+        }]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn trim_movie_titles_and_person_names() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    let movie = create_movie(&db, &(), movie_titled("  Alien  ")).await?;
+    let person = create_person(
+        &db,
+        &(),
+        person::Model {
+            id: 0,
+            name: " Ridley Scott\n".to_owned(),
+        },
+    )
+    .await?;
+
+    // act
+    let patched = update_movie_partial(
+        &db,
+        &(),
+        movie.id,
+        PartialMovie {
+            title: Some("\tAliens ".to_owned()),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let renamed = update_person_partial(
+        &db,
+        &(),
+        person.id,
+        PartialPerson {
+            name: Some(" Sigourney Weaver ".to_owned()),
+        },
+    )
+    .await?;
+
+    // assert
+    assert_eq!(movie.title, "Alien");
+    assert_eq!(person.name, "Ridley Scott");
+    assert_eq!(patched.title, "Aliens");
+    assert_eq!(renamed.name, "Sigourney Weaver");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn credit_persons_on_movies() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    let alien = create_movie(&db, &(), movie_titled("Alien")).await?;
+    let scott = create_person(
+        &db,
+        &(),
+        person::Model {
+            id: 0,
+            name: "Ridley Scott".to_owned(),
+        },
+    )
+    .await?;
+    let weaver = create_person(
+        &db,
+        &(),
+        person::Model {
+            id: 0,
+            name: "Sigourney Weaver".to_owned(),
+        },
+    )
+    .await?;
+
+    let credit = |person_id, r#type| credit::Model {
+        id: 0,
+        movie_id: alien.id,
+        person_id,
+        r#type,
+    };
+
+    // act
+    let directed = create_credit(&db, credit(scott.id, CreditType::Director)).await?;
+    let acted = create_credit(&db, credit(weaver.id, CreditType::Actor)).await?;
+    let missing_person = create_credit(&db, credit(-1, CreditType::Actor)).await;
+
+    let credits = get_movie_credits(&db, alien.id).await?.unwrap();
+    let filmography = get_person_filmography(&db, weaver.id).await?.unwrap();
+
+    // assert
+    assert_eq!(
+        credits.cast,
+        [MovieCredit {
+            credit_id: acted.id,
+            person_id: weaver.id,
+            name: "Sigourney Weaver".to_owned(),
+        }]
+    );
+    assert_eq!(
+        credits.directors,
+        [MovieCredit {
+            credit_id: directed.id,
+            person_id: scott.id,
+            name: "Ridley Scott".to_owned(),
+        }]
+    );
+    assert!(credits.producers.is_empty());
+
+    assert_eq!(filmography.len(), 1);
+    assert_eq!(filmography[0].movie, MovieResponse::from(alien));
+    assert_eq!(filmography[0].r#type, CreditType::Actor);
+
+    assert!(matches!(missing_person, Err(DbErr::RecordNotFound(_))));
+    assert_eq!(get_movie_credits(&db, -1).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn include_credits_and_persons_in_movies() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+
+    let alien = create_movie(&db, &(), movie_titled("Alien")).await?;
+    let dune = create_movie(&db, &(), movie_titled("Dune")).await?;
+    let scott = create_person(
+        &db,
+        &(),
+        person::Model {
+            id: 0,
+            name: "Ridley Scott".to_owned(),
+        },
+    )
+    .await?;
+
+    let credit = create_credit(
+        &db,
+        credit::Model {
+            id: 0,
+            movie_id: alien.id,
+            person_id: scott.id,
+            r#type: CreditType::Director,
+        },
+    )
+    .await?;
+
+    let includes = MovieIncludes {
+        credits: true,
+        credit_persons: true,
+        ..Default::default()
+    };
+
+    // act
+    let bare = load_movie_includes(&db, vec![alien.clone()], MovieIncludes::default()).await?;
+    let expanded = load_movie_includes(&db, vec![alien.clone(), dune.clone()], includes).await?;
+
+    // assert
+    assert_eq!(bare[0].credits, None);
+
+    assert_eq!(expanded[0].movie, MovieResponse::from(alien));
+    let credits = expanded[0].credits.as_ref().unwrap();
+    assert_eq!(credits.len(), 1);
+    assert_eq!(credits[0].id, credit.id);
+    assert_eq!(credits[0].person, Some(scott));
+
+    assert_eq!(expanded[1].movie, MovieResponse::from(dune));
+    assert_eq!(expanded[1].credits, Some(vec![]));
+
+    Ok(())
+}
+
+#[test]
+fn validate_movie_input() {
+    // arrange
+    let valid = CreateMovie {
+        poster_url: "https://example.com/alien.jpg".to_owned(),
+        ..movie_titled("Alien")
+    };
+    let invalid = CreateMovie {
+        title: "  ".to_owned(),
+        release_date: Utc.with_ymd_and_hms(1700, 1, 1, 0, 0, 0).unwrap(),
+        poster_url: "alien.jpg".to_owned(),
+        description: Default::default(),
+        rating: 11,
+    };
+    let partial = PartialMovie {
+        rating: Some(-1),
+        ..Default::default()
+    };
+
+    // act
+    let valid = valid.validate_on(&RatingScale::default());
+    let invalid = invalid.validate_on(&RatingScale::default());
+    let partial = partial.validate_on(&RatingScale::default());
+
+    // assert
+    assert_eq!(valid, Ok(()));
+
+    let fields: Vec<_> = invalid
+        .unwrap_err()
+        .into_iter()
+        .map(|error| error.field)
+        .collect();
+    assert_eq!(fields, ["title", "release_date", "poster_url", "rating"]);
+
+    assert_eq!(
+        partial,
+        Err(vec![FieldError {
+            field: "rating".to_owned(),
+            message: "must be between 0 and 10".to_owned(),
+        }])
+    );
+}
+
+#[test]
+fn validate_ratings_on_configured_scale() {
+    // arrange
+    let scale = RatingScale::new(1, 5).unwrap();
+    let rate = |score| RateMovie { score };
+
+    // act
+    let in_scale = [1, 5].map(|score| rate(score).validate_on(&scale));
+    let out_of_scale = [0, 6].map(|score| rate(score).validate_on(&scale));
+
+    // assert
+    assert_eq!(in_scale, [Ok(()), Ok(())]);
+    for result in out_of_scale {
+        assert_eq!(
+            result,
+            Err(vec![FieldError {
+                field: "score".to_owned(),
+                message: "must be between 1 and 5".to_owned(),
+            }])
+        );
+    }
+    assert!(RatingScale::new(0, 11).is_err());
+    assert!(RatingScale::new(-1, 5).is_err());
+    assert!(RatingScale::new(5, 5).is_err());
+}
+
+#[test]
+fn validate_poster_urls_as_stored() {
+    // arrange
+    let poster = |poster_url: &str| PartialMovie {
+        poster_url: Some(poster_url.to_owned()),
+        ..Default::default()
+    };
+
+    // act
+    let valid = ["", "https://example.com/alien.jpg", "HTTP://example.com"].map(poster);
+    let invalid = [
+        " https://example.com/alien.jpg",
+        "https://example.com/alien.jpg ",
+        "https:example.com/alien.jpg",
+        "https:///example.com/alien.jpg",
+        "ftp://example.com/alien.jpg",
+        "https://",
+    ]
+    .map(poster);
+
+    // assert
+    for movie in valid {
+        assert_eq!(
+            movie.validate_on(&RatingScale::default()),
+            Ok(()),
+            "{:?}",
+            movie.poster_url
+        );
+    }
+    for movie in invalid {
+        assert!(
+            movie.validate_on(&RatingScale::default()).is_err(),
+            "{:?}",
+            movie.poster_url
+        );
+    }
+}
+
+#[test]
+fn validate_movie_filters_and_ids() {
+    // arrange
+    let valid = MovieFilter {
+        min_rating: Some(3),
+        max_rating: Some(3),
+        ..Default::default()
+    };
+    let invalid = MovieFilter {
+        released_after: Some(Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap()),
+        released_before: Some(Utc.with_ymd_and_hms(1990, 1, 1, 0, 0, 0).unwrap()),
+        min_rating: Some(8),
+        max_rating: Some(11),
+        genres: Some(TaxonomyFilter {
+            ids: vec![1, 0],
+            matching: TaxonomyMatch::Any,
+        }),
+        ..Default::default()
+    };
+
+    // act
+    let valid = valid.validate_on(&RatingScale::default());
+    let invalid = invalid.validate_on(&RatingScale::default());
+    let ids = validate_ids(&[("id", 1), ("genre_id", -2)]);
+
+    // assert
+    assert_eq!(valid, Ok(()));
+
+    let fields: Vec<_> = invalid
+        .unwrap_err()
+        .into_iter()
+        .map(|error| error.field)
+        .collect();
+    assert_eq!(fields, ["max_rating", "released_before", "genre"]);
+
+    assert_eq!(
+        ids,
+        Err(vec![FieldError {
+            field: "genre_id".to_owned(),
+            message: "must be a positive id".to_owned(),
+        }])
+    );
+}
+
+#[tokio::test]
+async fn find_api_keys_until_revoked() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let created = create_api_key(&db, "importer".to_owned(), ApiKeyScope::Write).await?;
+
+    // act
+    let found = find_api_key(&db, &created.key).await?;
+    let wrong = find_api_key(&db, &format!("{}x", created.key)).await?;
+    let revoked = revoke_api_key(&db, created.api_key.id).await?;
+    let found_after_revoke = find_api_key(&db, &created.key).await?;
+
+    // assert
+    assert!(created.key.starts_with(&created.api_key.prefix));
+    assert_ne!(created.api_key.key_hash, created.key);
+    assert_eq!(found, Some(created.api_key));
+    assert_eq!(wrong, None);
+    assert!(revoked.revoked_at.is_some());
+    assert_eq!(found_after_revoke, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn log_users_in_until_their_session_ends() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let password_hash = hash_password("correct horse");
+    let user = create_user(&db, " Jane@Example.com ", "Jane".to_owned(), password_hash).await?;
+    let expired = create_session(&db, user.id, chrono::Duration::seconds(-1)).await?;
+
+    // act
+    let found = find_user_by_email(&db, "jane@example.COM").await?;
+    let session = create_session(&db, user.id, chrono::Duration::hours(1)).await?;
+    let logged_in = find_session_user(&db, &session.token).await?;
+    let logged_in_expired = find_session_user(&db, &expired.token).await?;
+    delete_session(&db, &session.token).await?;
+    let logged_out = find_session_user(&db, &session.token).await?;
+
+    // assert
+    assert_eq!(user.email, "jane@example.com");
+    let password_hash = user.password_hash.as_deref().unwrap();
+    assert!(verify_password("correct horse", password_hash));
+    assert!(!verify_password("wrong horse", password_hash));
+    assert_eq!(found, Some(user.clone()));
+    assert_eq!(logged_in, Some(user));
+    assert_eq!(logged_in_expired, None);
+    assert_eq!(logged_out, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn manage_user_roles() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let user = create_user(
+        &db,
+        "jane@example.com",
+        "Jane".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let session = create_session(&db, user.id, chrono::Duration::hours(1)).await?;
+
+    // act
+    let promoted = update_user_role(&db, user.id, UserRole::Editor).await?;
+    let missing = update_user_role(&db, user.id + 1, UserRole::Admin).await;
+    let deleted = delete_user(&db, user.id).await?;
+    let remaining = get_all_users(&db).await?;
+
+    // assert
+    assert_eq!(user.role, UserRole::Viewer);
+    assert_eq!(promoted.role, UserRole::Editor);
+    assert!(UserRole::Admin.grants(promoted.role));
+    assert!(!UserRole::Viewer.grants(promoted.role));
+    assert_eq!(ApiKeyScope::Write.role(), UserRole::Editor);
+    assert!(matches!(missing, Err(DbErr::RecordNotFound(_))));
+    assert_eq!(deleted.rows_affected, 1);
+    assert!(remaining.is_empty());
+    assert_eq!(find_session_user(&db, &session.token).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn count_user_ratings_in_movie_histograms() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let movie = create_movie(&db, &(), movie_titled("Alien")).await?;
+    let jane = create_user(
+        &db,
+        "jane@example.com",
+        "Jane".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let john = create_user(
+        &db,
+        "john@example.com",
+        "John".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let summary = |movie: Option<Model>| MovieResponse::from(movie.unwrap()).user_ratings;
+
+    // act
+    rate_movie(&db, jane.id, movie.id, 8).await?;
+    rate_movie(&db, john.id, movie.id, 6).await?;
+    rate_movie(&db, jane.id, movie.id, 10).await?;
+    let rated = summary(get_movie(&db, movie.id).await?);
+    let jane_ratings = get_user_ratings(&db, jane.id).await?;
+    let missing = rate_movie(&db, jane.id, movie.id + 1, 5).await;
+
+    let unrated = unrate_movie(&db, john.id, movie.id).await?;
+    let unrated_again = unrate_movie(&db, john.id, movie.id).await?;
+    delete_user(&db, jane.id).await?;
+    let emptied = summary(get_movie(&db, movie.id).await?);
+
+    // assert
+    assert_eq!(
+        rated,
+        RatingSummary {
+            average: Some(8.0),
+            count: 2,
+            histogram: vec![0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1],
+        }
+    );
+    assert_eq!(jane_ratings.len(), 1);
+    assert_eq!(jane_ratings[0].score, 10);
+    assert!(matches!(missing, Err(DbErr::RecordNotFound(_))));
+    assert_eq!(unrated.rows_affected, 1);
+    assert_eq!(unrated_again.rows_affected, 0);
+    assert_eq!(
+        emptied,
+        RatingSummary {
+            average: None,
+            count: 0,
+            histogram: vec![0; 11],
+        }
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn keep_list_items_in_order() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let jane = create_user(
+        &db,
+        "jane@example.com",
+        "Jane".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let john = create_user(
+        &db,
+        "john@example.com",
+        "John".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let [alien, aliens, heat] = [
+        create_movie(&db, &(), movie_titled("Alien")).await?,
+        create_movie(&db, &(), movie_titled("Aliens")).await?,
+        create_movie(&db, &(), movie_titled("Heat")).await?,
+    ];
+    let watchlist = create_list(
+        &db,
+        jane.id,
+        CreateList {
+            name: "To watch".to_owned(),
+            description: String::new(),
+            visibility: ListVisibility::Private,
+        },
+    )
+    .await?;
+    let best = create_list(
+        &db,
+        john.id,
+        CreateList {
+            name: "Best of the 80s".to_owned(),
+            description: String::new(),
+            visibility: ListVisibility::Public,
+        },
+    )
+    .await?;
+    let add = |movie_id, position| AddListItem {
+        movie_id,
+        note: String::new(),
+        position,
+    };
+    let movie_ids = |items: &[ListItemResponse]| -> Vec<(i32, i32)> {
+        items
+            .iter()
+            .map(|item| (item.position, item.movie.id))
+            .collect()
+    };
+
+    // act
+    let first = add_list_item(&db, watchlist.id, add(alien.id, None)).await?;
+    add_list_item(&db, watchlist.id, add(heat.id, None)).await?;
+    let second = add_list_item(&db, watchlist.id, add(aliens.id, Some(1))).await?;
+    let duplicate = add_list_item(&db, watchlist.id, add(alien.id, None)).await;
+    let added = get_list_items(&db, watchlist.id).await?;
+
+    remove_list_item(&db, watchlist.id, first.id).await?;
+    let removed = get_list_items(&db, watchlist.id).await?;
+
+    let reordered = reorder_list(
+        &db,
+        watchlist.id,
+        ReorderList {
+            item_ids: removed.iter().rev().map(|item| item.id).collect(),
+        },
+    )
+    .await?;
+    let incomplete = reorder_list(
+        &db,
+        watchlist.id,
+        ReorderList {
+            item_ids: vec![second.id],
+        },
+    )
+    .await?;
+    let after_reorder = get_list_items(&db, watchlist.id).await?;
+
+    let anonymous_lists = get_visible_lists(&db, None, Pagination::default()).await?;
+    let jane_lists = get_visible_lists(&db, Some(jane.id), Pagination::default()).await?;
+
+    // assert
+    assert_eq!(
+        movie_ids(&added),
+        [(0, alien.id), (1, aliens.id), (2, heat.id)]
+    );
+    assert!(duplicate.is_err());
+    assert_eq!(movie_ids(&removed), [(0, aliens.id), (1, heat.id)]);
+    assert_eq!(reordered.map(|items| items.len()), Some(2));
+    assert_eq!(incomplete, None);
+    assert_eq!(movie_ids(&after_reorder), [(0, heat.id), (1, aliens.id)]);
+    assert_eq!(anonymous_lists.items, std::slice::from_ref(&best));
+    assert_eq!(
+        jane_lists
+            .items
+            .iter()
+            .map(|list| list.id)
+            .collect::<Vec<_>>(),
+        [watchlist.id, best.id]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn link_oidc_identities_to_users() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let jane = create_user(
+        &db,
+        "jane@example.com",
+        "Jane".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let identity = |subject: &str, email: &str, email_verified, role| OidcIdentity {
+        issuer: "https://idp.example.com".to_owned(),
+        subject: subject.to_owned(),
+        email: email.to_owned(),
+        email_verified,
+        display_name: "From IdP".to_owned(),
+        role,
+    };
+
+    // act
+    let linked = upsert_oidc_user(
+        &db,
+        identity("jane", "Jane@example.com", true, Some(UserRole::Editor)),
+    )
+    .await?;
+    let logged_in_again =
+        upsert_oidc_user(&db, identity("jane", "jane@example.com", true, None)).await?;
+    let created = upsert_oidc_user(&db, identity("john", "john@example.com", false, None)).await?;
+    let unverified =
+        upsert_oidc_user(&db, identity("mallory", "john@example.com", false, None)).await;
+
+    // assert
+    assert_eq!(linked.id, jane.id);
+    assert_eq!(linked.role, UserRole::Editor);
+    assert_eq!(linked.oidc_subject.as_deref(), Some("jane"));
+    assert_eq!(linked.password_hash, None);
+    assert_eq!(logged_in_again, linked);
+    assert_ne!(created.id, jane.id);
+    assert_eq!(created.role, UserRole::Viewer);
+    assert_eq!(created.password_hash, None);
+    assert!(unverified.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn linking_oidc_identities_locks_out_pre_registered_users() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let squatter = create_user(
+        &db,
+        "jane@example.com",
+        "Not Jane".to_owned(),
+        hash_password("squatter password"),
+    )
+    .await?;
+    let session = create_session(&db, squatter.id, chrono::Duration::hours(1)).await?;
+    let identity = OidcIdentity {
+        issuer: "https://idp.example.com".to_owned(),
+        subject: "jane".to_owned(),
+        email: "jane@example.com".to_owned(),
+        email_verified: true,
+        display_name: "Jane".to_owned(),
+        role: None,
+    };
+
+    // act
+    let linked = upsert_oidc_user(&db, identity).await?;
+    let squatter_session = find_session_user(&db, &session.token).await?;
+    let found = find_user_by_email(&db, "jane@example.com").await?;
+
+    // assert
+    assert_eq!(linked.id, squatter.id);
+    assert_eq!(linked.display_name, "Jane");
+    assert_eq!(linked.password_hash, None);
+    assert_eq!(squatter_session, None);
+    assert_eq!(found.and_then(|user| user.password_hash), None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn moderate_reviews() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let jane = create_user(
+        &db,
+        "jane@example.com",
+        "Jane".to_owned(),
+        hash_password("x"),
+    )
+    .await?;
+    let alien = create_movie(&db, &(), movie_titled("Alien")).await?;
+    let review = CreateReview {
+        movie_id: alien.id,
+        title: "In space".to_owned(),
+        body: "No one can hear you **scream**.<script>alert(1)</script>\n\n[More](https://example.com)"
+            .to_owned(),
+        spoiler: false,
+    };
+
+    // act
+    let pending = create_review(&db, jane.id, review.clone()).await?;
+    let queue = get_pending_reviews(&db, Pagination::default()).await?;
+    let before_publishing = get_movie_reviews(&db, alien.id).await?;
+
+    let published = moderate_review(&db, pending.id, ReviewStatus::Published).await?;
+    let republished = moderate_review(&db, pending.id, ReviewStatus::Published).await?;
+    let after_publishing = get_movie_reviews(&db, alien.id).await?;
+
+    let edited = update_review(
+        &db,
+        pending.id,
+        PartialReview {
+            spoiler: Some(true),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let after_editing = get_published_reviews(&db, Some(alien.id), Pagination::default()).await?;
+
+    let missing_movie = create_review(
+        &db,
+        jane.id,
+        CreateReview {
+            movie_id: alien.id + 1,
+            ..review
+        },
+    )
+    .await;
+
+    // assert
+    assert_eq!(pending.status, ReviewStatus::Pending);
+    assert_eq!(
+        pending.body_html,
+        "<p>No one can hear you <strong>scream</strong>.</p>\n<p><a href=\"https://example.com\" \
+         rel=\"nofollow noopener noreferrer\">More</a></p>\n"
+    );
+    assert_eq!(queue.items, std::slice::from_ref(&pending));
+    assert_eq!(before_publishing.count, 0);
+
+    let published = published.expect("pending reviews can be published");
+    assert_eq!(published.status, ReviewStatus::Published);
+    assert_eq!(republished, None);
+    assert_eq!(after_publishing.count, 1);
+    assert_eq!(after_publishing.latest, [published]);
+
+    assert_eq!(edited.status, ReviewStatus::Pending);
+    assert!(edited.spoiler);
+    assert_eq!(edited.body, pending.body);
+    assert!(after_editing.items.is_empty());
+
+    assert!(matches!(missing_movie, Err(DbErr::RecordNotFound(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn filter_movies_by_genres_and_keywords() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let [alien, aliens, heat] = [
+        create_movie(&db, &(), movie_titled("Alien")).await?,
+        create_movie(&db, &(), movie_titled("Aliens")).await?,
+        create_movie(&db, &(), movie_titled("Heat")).await?,
+    ];
+    let genre = |name: &str| CreateGenre {
+        name: name.to_owned(),
+    };
+    let [horror, action, crime] = [
+        create_genre(&db, genre("Horror")).await?,
+        create_genre(&db, genre("Action")).await?,
+        create_genre(&db, genre("Crime")).await?,
+    ];
+    let space = create_keyword(
+        &db,
+        CreateKeyword {
+            name: " Space ".to_owned(),
+        },
+    )
+    .await?;
+
+    assign_genre(&db, alien.id, horror.id).await?;
+    assign_genre(&db, aliens.id, horror.id).await?;
+    assign_genre(&db, aliens.id, action.id).await?;
+    assign_genre(&db, aliens.id, action.id).await?;
+    assign_genre(&db, heat.id, action.id).await?;
+    assign_genre(&db, heat.id, crime.id).await?;
+    unassign_genre(&db, heat.id, crime.id).await?;
+    assign_keyword(&db, alien.id, space.id).await?;
+    assign_keyword(&db, aliens.id, space.id).await?;
+
+    let titles = |page: movies_core::Page<Model>| -> Vec<String> {
+        page.items.into_iter().map(|movie| movie.title).collect()
+    };
+    let with_genres = |matching| MovieFilter {
+        genres: Some(TaxonomyFilter {
+            ids: vec![horror.id, action.id],
+            matching,
+        }),
+        ..Default::default()
+    };
+
+    // act
+    let any = get_all_movies(
+        &db,
+        &with_genres(TaxonomyMatch::Any),
+        MovieSort::default(),
+        Pagination::default(),
+    )
+    .await?;
+    let all = get_all_movies(
+        &db,
+        &with_genres(TaxonomyMatch::All),
+        MovieSort::default(),
+        Pagination::default(),
+    )
+    .await?;
+    let action_in_space = get_all_movies(
+        &db,
+        &MovieFilter {
+            genres: Some(TaxonomyFilter {
+                ids: vec![action.id],
+                matching: TaxonomyMatch::All,
+            }),
+            keywords: Some(TaxonomyFilter {
+                ids: vec![space.id],
+                matching: TaxonomyMatch::Any,
+            }),
+            ..Default::default()
+        },
+        MovieSort::default(),
+        Pagination::default(),
+    )
+    .await?;
+    let missing_genre = assign_genre(&db, alien.id, crime.id + 1).await;
+    let expanded = load_movie_includes(
+        &db,
+        vec![aliens.clone(), heat.clone()],
+        MovieIncludes {
+            genres: true,
+            keywords: true,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // assert
+    assert_eq!(space.name, "space");
+    assert_eq!(titles(any), ["Alien", "Aliens", "Heat"]);
+    assert_eq!(titles(all), ["Aliens"]);
+    assert_eq!(titles(action_in_space), ["Aliens"]);
+    assert!(matches!(missing_genre, Err(DbErr::RecordNotFound(_))));
+
+    assert_eq!(expanded[0].genres, Some(vec![action.clone(), horror]));
+    assert_eq!(expanded[0].keywords, Some(vec![space]));
+    assert_eq!(expanded[1].genres, Some(vec![action]));
+    assert_eq!(expanded[1].keywords, Some(vec![]));
+    assert_eq!(expanded[0].credits, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_movies_and_persons() -> Result<(), DbErr> {
+    // arrange
+    let db = prepare_test_db().await?;
+    let alien = create_movie(&db, &(), movie_titled("Alien")).await?;
+    let dark_star = create_movie(
+        &db,
+        &(),
+        CreateMovie {
+            description: "A <crew> meets an alien on the way home".to_owned(),
+            ..movie_titled("Dark Star")
+        },
+    )
+    .await?;
+    let person = create_person(
+        &db,
+        &(),
+        person::Model {
+            id: 0,
+            name: "Alien Ant Farm".to_owned(),
+        },
+    )
+    .await?;
+    create_movie(&db, &(), movie_titled("Heat")).await?;
+
+    // act
+    let hits = search(&db, " ALIEN ", 10).await?;
+    let limited = search(&db, "alien", 1).await?;
+    let escaped = search(&db, "%", 10).await?;
+
+    // assert
+    let found: Vec<_> = hits.iter().map(|hit| (hit.r#type, hit.id)).collect();
+    assert_eq!(
+        found,
+        [
+            (SearchHitType::Movie, alien.id),
+            (SearchHitType::Person, person.id),
+            (SearchHitType::Movie, dark_star.id),
+        ]
+    );
+    assert_eq!(hits[0].snippet, "<mark>Alien</mark>");
+    assert_eq!(hits[0].year, Some(2000));
+    assert_eq!(hits[1].year, None);
+    assert_eq!(
+        hits[2].snippet,
+        "A &lt;crew&gt; meets an <mark>alien</mark> on the way home"
+    );
+    assert_eq!(limited.len(), 1);
+    assert!(escaped.is_empty());
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use sea_orm::sea_query::Index;
 use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, DbErr, Schema};
 
 pub async fn prepare_test_db() -> Result<DatabaseConnection, DbErr> {
@@ -9,6 +10,17 @@ pub async fn prepare_test_db() -> Result<DatabaseConnection, DbErr> {
         schema.create_table_from_entity(movies_entity::prelude::Movie),
         schema.create_table_from_entity(movies_entity::prelude::Person),
         schema.create_table_from_entity(movies_entity::prelude::Credit),
+        schema.create_table_from_entity(movies_entity::prelude::ApiKey),
+        schema.create_table_from_entity(movies_entity::prelude::User),
+        schema.create_table_from_entity(movies_entity::prelude::Session),
+        schema.create_table_from_entity(movies_entity::prelude::UserRating),
+        schema.create_table_from_entity(movies_entity::prelude::List),
+        schema.create_table_from_entity(movies_entity::prelude::ListItem),
+        schema.create_table_from_entity(movies_entity::prelude::Review),
+        schema.create_table_from_entity(movies_entity::prelude::Genre),
+        schema.create_table_from_entity(movies_entity::prelude::Keyword),
+        schema.create_table_from_entity(movies_entity::prelude::MovieGenre),
+        schema.create_table_from_entity(movies_entity::prelude::MovieKeyword),
     ];
 
     for statement in statements {
@@ -16,5 +28,18 @@ pub async fn prepare_test_db() -> Result<DatabaseConnection, DbErr> {
             .await?;
     }
 
+    // Constraints spanning several columns, which entities cannot declare
+    let indexes = [Index::create()
+        .name("idx_list_item_list_id_movie_id")
+        .table(movies_entity::list_item::Entity)
+        .col(movies_entity::list_item::Column::ListId)
+        .col(movies_entity::list_item::Column::MovieId)
+        .unique()
+        .to_owned()];
+
+    for index in indexes {
+        db.execute(db.get_database_backend().build(&index)).await?;
+    }
+
     Ok(db)
 }
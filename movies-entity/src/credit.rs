@@ -3,8 +3,10 @@
 use super::sea_orm_active_enums::CreditType;
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, ToSchema)]
+#[schema(as = Credit)]
 #[sea_orm(table_name = "credit")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -2,7 +2,18 @@
 
 pub mod prelude;
 
+pub mod api_key;
 pub mod credit;
+pub mod genre;
+pub mod keyword;
+pub mod list;
+pub mod list_item;
 pub mod movie;
+pub mod movie_genre;
+pub mod movie_keyword;
 pub mod person;
+pub mod review;
 pub mod sea_orm_active_enums;
+pub mod session;
+pub mod user;
+pub mod user_rating;
@@ -1,28 +1,45 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.11
 
 use sea_orm::entity::prelude::*;
+use sea_orm::FromJsonQueryResult;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
-#[schema(as = Movie)]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "movie")]
 pub struct Model {
     #[sea_orm(primary_key)]
-    #[serde(skip_deserializing)]
     pub id: i32,
     pub title: String,
     pub release_date: chrono::DateTime<chrono::Utc>,
     pub poster_url: String,
     #[sea_orm(column_type = "Text")]
     pub description: String,
+    /// Rating given by the editors, unrelated to the ratings of users
     pub rating: i32,
+    /// Kept in step with the `user_rating` table
+    #[sea_orm(column_type = "JsonBinary")]
+    pub user_rating_histogram: RatingHistogram,
 }
 
+/// Number of user ratings of a movie with each score, indexed by score and without trailing
+/// zeros.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct RatingHistogram(pub Vec<i32>);
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::credit::Entity")]
     Credit,
+    #[sea_orm(has_many = "super::list_item::Entity")]
+    ListItem,
+    #[sea_orm(has_many = "super::movie_genre::Entity")]
+    MovieGenre,
+    #[sea_orm(has_many = "super::movie_keyword::Entity")]
+    MovieKeyword,
+    #[sea_orm(has_many = "super::review::Entity")]
+    Review,
+    #[sea_orm(has_many = "super::user_rating::Entity")]
+    UserRating,
 }
 
 impl Related<super::credit::Entity> for Entity {
@@ -31,4 +48,54 @@ impl Related<super::credit::Entity> for Entity {
     }
 }
 
+impl Related<super::list_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ListItem.def()
+    }
+}
+
+impl Related<super::movie_genre::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MovieGenre.def()
+    }
+}
+
+impl Related<super::movie_keyword::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MovieKeyword.def()
+    }
+}
+
+impl Related<super::genre::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::movie_genre::Relation::Genre.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::movie_genre::Relation::Movie.def().rev())
+    }
+}
+
+impl Related<super::keyword::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::movie_keyword::Relation::Keyword.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::movie_keyword::Relation::Movie.def().rev())
+    }
+}
+
+impl Related<super::review::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Review.def()
+    }
+}
+
+impl Related<super::user_rating::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserRating.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,16 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.11
 
+pub use super::api_key::Entity as ApiKey;
 pub use super::credit::Entity as Credit;
+pub use super::genre::Entity as Genre;
+pub use super::keyword::Entity as Keyword;
+pub use super::list::Entity as List;
+pub use super::list_item::Entity as ListItem;
 pub use super::movie::Entity as Movie;
+pub use super::movie_genre::Entity as MovieGenre;
+pub use super::movie_keyword::Entity as MovieKeyword;
 pub use super::person::Entity as Person;
+pub use super::review::Entity as Review;
+pub use super::session::Entity as Session;
+pub use super::user::Entity as User;
+pub use super::user_rating::Entity as UserRating;
@@ -1,9 +1,67 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.11
 
+use std::str::FromStr;
+
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What an API key may do, each scope granting the ones before it as well.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "api_key_scope")]
+pub enum ApiKeyScope {
+    #[sea_orm(string_value = "read")]
+    Read,
+    #[sea_orm(string_value = "write")]
+    Write,
+    #[sea_orm(string_value = "admin")]
+    Admin,
+}
+
+impl ApiKeyScope {
+    /// Role of the callers using a key with this scope.
+    pub fn role(self) -> UserRole {
+        match self {
+            ApiKeyScope::Read => UserRole::Viewer,
+            ApiKeyScope::Write => UserRole::Editor,
+            ApiKeyScope::Admin => UserRole::Admin,
+        }
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = String;
 
-#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "read" => Ok(ApiKeyScope::Read),
+            "write" => Ok(ApiKeyScope::Write),
+            "admin" => Ok(ApiKeyScope::Admin),
+            _ => Err(format!(
+                "unknown scope `{value}`, expected `read`, `write` or `admin`"
+            )),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "credit_type")]
 pub enum CreditType {
     #[sea_orm(string_value = "actor")]
@@ -13,3 +71,123 @@ pub enum CreditType {
     #[sea_orm(string_value = "producer")]
     Producer,
 }
+
+/// Who may see a list of movies.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "list_visibility")]
+pub enum ListVisibility {
+    /// Only its owner
+    #[default]
+    #[sea_orm(string_value = "private")]
+    Private,
+    /// Everyone, including anonymous callers
+    #[sea_orm(string_value = "public")]
+    Public,
+}
+
+/// Where a review is in moderation.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "review_status")]
+pub enum ReviewStatus {
+    /// Waiting for a moderator, only seen by its author and moderators
+    #[default]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    /// Approved by a moderator, seen by everyone
+    #[sea_orm(string_value = "published")]
+    Published,
+    /// Rejected by a moderator, only seen by its author and moderators
+    #[sea_orm(string_value = "hidden")]
+    Hidden,
+}
+
+impl ReviewStatus {
+    /// Whether a moderator may move a review from this status to `next`. Reviews only go back to
+    /// pending when their author edits them.
+    pub fn may_become(self, next: ReviewStatus) -> bool {
+        matches!(
+            (self, next),
+            (
+                ReviewStatus::Pending,
+                ReviewStatus::Published | ReviewStatus::Hidden
+            ) | (ReviewStatus::Published, ReviewStatus::Hidden)
+                | (ReviewStatus::Hidden, ReviewStatus::Published)
+        )
+    }
+}
+
+/// What a user may do, each role granting what the ones before it may do as well.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "user_role")]
+pub enum UserRole {
+    #[default]
+    #[sea_orm(string_value = "viewer")]
+    Viewer,
+    #[sea_orm(string_value = "editor")]
+    Editor,
+    #[sea_orm(string_value = "admin")]
+    Admin,
+}
+
+impl UserRole {
+    /// Whether this role may do what `required` may.
+    pub fn grants(self, required: UserRole) -> bool {
+        self >= required
+    }
+}
+
+impl FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "viewer" => Ok(UserRole::Viewer),
+            "editor" => Ok(UserRole::Editor),
+            "admin" => Ok(UserRole::Admin),
+            _ => Err(format!(
+                "unknown role `{value}`, expected `viewer`, `editor` or `admin`"
+            )),
+        }
+    }
+}
@@ -59,6 +59,12 @@ impl Parse for VariantResponseArgs {
             return Err(Error::new(first_span, MISSING_STATUS_ERROR));
         }
 
+        // Leave any other arguments, such as `content_type`, to `utoipa::IntoResponses`
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            input.parse::<TokenStream>()?;
+        }
+
         Ok(Self { status })
     }
 }
@@ -72,6 +78,7 @@ pub struct IntoResponse {
 impl ToTokens for IntoResponse {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ident = &self.ident;
+        let (impl_generics, type_generics, where_clause) = self.generics.split_for_impl();
 
         let responses = match &self.data {
             Data::Enum(data_enum) => data_enum.variants.iter().map(|variant| {
@@ -127,7 +134,7 @@ impl ToTokens for IntoResponse {
         };
 
         tokens.extend(quote! {
-            impl IntoResponse for #ident {
+            impl #impl_generics IntoResponse for #ident #type_generics #where_clause {
                 fn into_response(self) -> axum::response::Response {
                     match self {
                         #(#responses),*
@@ -10,6 +10,7 @@ use movies_macros::IntoResponse;
 ///         TestEnum::OneField(body) => IntoResponse::into_response((axum::http::StatusCode::OK, body)),
 ///         TestEnum::ZeroFields() => IntoResponse::into_response(axum::http::StatusCode::OK),
 ///         TestEnum::NoFields => IntoResponse::into_response(axum::http::StatusCode::OK),
+///         TestEnum::ExtraArguments(body) => IntoResponse::into_response((axum::http::StatusCode::CONFLICT, body)),
 ///     }
 ///   }
 /// }
@@ -27,6 +28,9 @@ enum TestEnum {
 
     #[response(status = INTERNAL_SERVER_ERROR)]
     NoFields,
+
+    #[response(status = CONFLICT, content_type = "text/plain")]
+    ExtraArguments(&'static str),
 }
 
 #[test]
@@ -56,3 +60,23 @@ fn no_fields_into_response_works() {
 
     assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
 }
+
+#[test]
+fn extra_arguments_into_response_works() {
+    let response = TestEnum::ExtraArguments("conflict").into_response();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[derive(IntoResponse)]
+enum GenericTestEnum<T: IntoResponse> {
+    #[response(status = OK)]
+    Success(T),
+}
+
+#[test]
+fn generic_into_response_works() {
+    let response = GenericTestEnum::Success("hello").into_response();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -4,6 +4,15 @@ mod m20220101_000001_create_table;
 mod m20230603_104409_alter_release_date;
 mod m20240117_090322_create_person_table;
 mod m20240117_092050_create_credit_table;
+mod m20261018_120000_add_check_constraints;
+mod m20261018_130000_create_api_key_table;
+mod m20261018_140000_create_user_and_session_tables;
+mod m20261018_150000_add_roles_and_oidc_to_user;
+mod m20261018_160000_create_user_rating_table;
+mod m20261018_170000_create_list_tables;
+mod m20261018_180000_create_review_table;
+mod m20261018_190000_create_genre_and_keyword_tables;
+mod m20261018_200000_add_search_vectors;
 
 pub struct Migrator;
 
@@ -15,6 +24,15 @@ impl MigratorTrait for Migrator {
             Box::new(m20230603_104409_alter_release_date::Migration),
             Box::new(m20240117_090322_create_person_table::Migration),
             Box::new(m20240117_092050_create_credit_table::Migration),
+            Box::new(m20261018_120000_add_check_constraints::Migration),
+            Box::new(m20261018_130000_create_api_key_table::Migration),
+            Box::new(m20261018_140000_create_user_and_session_tables::Migration),
+            Box::new(m20261018_150000_add_roles_and_oidc_to_user::Migration),
+            Box::new(m20261018_160000_create_user_rating_table::Migration),
+            Box::new(m20261018_170000_create_list_tables::Migration),
+            Box::new(m20261018_180000_create_review_table::Migration),
+            Box::new(m20261018_190000_create_genre_and_keyword_tables::Migration),
+            Box::new(m20261018_200000_add_search_vectors::Migration),
         ]
     }
 }
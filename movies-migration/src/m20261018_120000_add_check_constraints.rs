@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{DbBackend, Statement};
+
+/// Needs Postgres, as SQLite cannot add constraints to existing tables.
+///
+/// Fails without changing anything while some rows break a constraint, listing them so that they
+/// can be fixed by hand before migrating again.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Constraints mirroring the input validation done in `movies_core::validation`, as a last line of
+/// defence against writes that bypass it: table, name and condition.
+///
+/// The bounds are written out rather than read from `movies_core`, so that replaying the migration
+/// always runs the SQL that was applied.
+const CONSTRAINTS: [(&str, &str, &str); 5] = [
+    ("movie", "movie_title_not_blank", "length(trim(title)) > 0"),
+    ("movie", "movie_rating_range", "rating BETWEEN 0 AND 10"),
+    (
+        "movie",
+        "movie_poster_url_absolute",
+        "poster_url = '' OR poster_url ~* '^https?://[^\\s/]+'",
+    ),
+    (
+        "movie",
+        "movie_release_date_range",
+        "release_date >= '1870-01-01T00:00:00Z' AND release_date < '2100-01-01T00:00:00Z'",
+    ),
+    ("person", "person_name_not_blank", "length(trim(name)) > 0"),
+];
+
+/// Most ids of offending rows listed in the error of a failed migration.
+const MAX_LISTED_IDS: usize = 20;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() != DbBackend::Postgres {
+            return Err(DbErr::Migration(
+                "Adding check constraints needs Postgres".to_owned(),
+            ));
+        }
+
+        let db = manager.get_connection();
+
+        let mut offending = vec![];
+        for (table, name, check) in CONSTRAINTS {
+            let rows = db
+                .query_all(Statement::from_string(
+                    DbBackend::Postgres,
+                    format!("SELECT id FROM \"{table}\" WHERE NOT ({check}) ORDER BY id"),
+                ))
+                .await?;
+
+            if rows.is_empty() {
+                continue;
+            }
+
+            let ids = rows
+                .iter()
+                .take(MAX_LISTED_IDS)
+                .map(|row| row.try_get::<i32>("", "id").map(|id| id.to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let more = if rows.len() > MAX_LISTED_IDS {
+                ", ..."
+            } else {
+                ""
+            };
+
+            offending.push(format!(
+                "{} rows of `{table}` break `{name}` ({check}), with ids {}{more}",
+                rows.len(),
+                ids.join(", ")
+            ));
+        }
+
+        if !offending.is_empty() {
+            return Err(DbErr::Migration(format!(
+                "Fix these rows before adding check constraints: {}",
+                offending.join("; ")
+            )));
+        }
+
+        for (table, name, check) in CONSTRAINTS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE \"{table}\" ADD CONSTRAINT \"{name}\" CHECK ({check})"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, name, _) in CONSTRAINTS.into_iter().rev() {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE \"{table}\" DROP CONSTRAINT \"{name}\""
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
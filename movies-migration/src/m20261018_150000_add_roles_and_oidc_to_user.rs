@@ -0,0 +1,104 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(UserRole::Table)
+                    .values([UserRole::Viewer, UserRole::Editor, UserRole::Admin])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .modify_column(ColumnDef::new(User::PasswordHash).string().null())
+                    .add_column(
+                        ColumnDef::new(User::Role)
+                            .enumeration(
+                                UserRole::Table,
+                                [UserRole::Viewer, UserRole::Editor, UserRole::Admin],
+                            )
+                            .not_null()
+                            .default(Expr::val("viewer").as_enum(UserRole::Table)),
+                    )
+                    .add_column(ColumnDef::new(User::OidcIssuer).string())
+                    .add_column(ColumnDef::new(User::OidcSubject).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_oidc_identity")
+                    .table(User::Table)
+                    .col(User::OidcIssuer)
+                    .col(User::OidcSubject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_user_oidc_identity")
+                    .table(User::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Users without a password cannot be kept
+        manager
+            .exec_stmt(
+                Query::delete()
+                    .from_table(User::Table)
+                    .and_where(Expr::col(User::PasswordHash).is_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::OidcSubject)
+                    .drop_column(User::OidcIssuer)
+                    .drop_column(User::Role)
+                    .modify_column(ColumnDef::new(User::PasswordHash).string().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(UserRole::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    PasswordHash,
+    Role,
+    OidcIssuer,
+    OidcSubject,
+}
+
+#[derive(DeriveIden)]
+pub enum UserRole {
+    Table,
+    Viewer,
+    Editor,
+    Admin,
+}
@@ -0,0 +1,114 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRating::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(UserRating::UserId).integer().not_null())
+                    .col(ColumnDef::new(UserRating::MovieId).integer().not_null())
+                    .col(
+                        ColumnDef::new(UserRating::Score)
+                            .integer()
+                            .not_null()
+                            .check(Expr::col(UserRating::Score).between(0, 10)),
+                    )
+                    .col(
+                        ColumnDef::new(UserRating::RatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(UserRating::UserId)
+                            .col(UserRating::MovieId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserRating::Table, UserRating::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserRating::Table, UserRating::MovieId)
+                            .to(Movie::Table, Movie::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The primary key only serves lookups by user
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_rating_movie_id")
+                    .table(UserRating::Table)
+                    .col(UserRating::MovieId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Kept in step with `user_rating` by `movies_core`, so that movies are returned along
+        // with their ratings without aggregating them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Movie::Table)
+                    .add_column(
+                        ColumnDef::new(Movie::UserRatingHistogram)
+                            .json_binary()
+                            .not_null()
+                            .default(Expr::cust("'[]'::jsonb")),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Movie::Table)
+                    .drop_column(Movie::UserRatingHistogram)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserRating::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserRating {
+    Table,
+    UserId,
+    MovieId,
+    Score,
+    RatedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Movie {
+    Table,
+    Id,
+    UserRatingHistogram,
+}
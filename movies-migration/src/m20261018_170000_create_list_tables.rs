@@ -0,0 +1,211 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ListVisibility::Table)
+                    .values([ListVisibility::Private, ListVisibility::Public])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(List::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(List::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(List::OwnerId).integer().not_null())
+                    .col(
+                        ColumnDef::new(List::Name)
+                            .string()
+                            .not_null()
+                            .check(Expr::cust("length(trim(name)) > 0")),
+                    )
+                    .col(ColumnDef::new(List::Description).text().not_null())
+                    .col(
+                        ColumnDef::new(List::Visibility)
+                            .enumeration(
+                                ListVisibility::Table,
+                                [ListVisibility::Private, ListVisibility::Public],
+                            )
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(List::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(List::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(List::Table, List::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_list_owner_id")
+                    .table(List::Table)
+                    .col(List::OwnerId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ListItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ListItem::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ListItem::ListId).integer().not_null())
+                    .col(ColumnDef::new(ListItem::MovieId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ListItem::Position)
+                            .integer()
+                            .not_null()
+                            .check(Expr::col(ListItem::Position).gte(0)),
+                    )
+                    .col(ColumnDef::new(ListItem::Note).text().not_null())
+                    .col(
+                        ColumnDef::new(ListItem::AddedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ListItem::Table, ListItem::ListId)
+                            .to(List::Table, List::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ListItem::Table, ListItem::MovieId)
+                            .to(Movie::Table, Movie::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A movie is on a list at most once, and items are read in order
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_list_item_list_id_movie_id")
+                    .table(ListItem::Table)
+                    .col(ListItem::ListId)
+                    .col(ListItem::MovieId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_list_item_list_id_position")
+                    .table(ListItem::Table)
+                    .col(ListItem::ListId)
+                    .col(ListItem::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_list_item_movie_id")
+                    .table(ListItem::Table)
+                    .col(ListItem::MovieId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ListItem::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(List::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ListVisibility::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum List {
+    Table,
+    Id,
+    OwnerId,
+    Name,
+    Description,
+    Visibility,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ListItem {
+    Table,
+    Id,
+    ListId,
+    MovieId,
+    Position,
+    Note,
+    AddedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum ListVisibility {
+    Table,
+    Private,
+    Public,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Movie {
+    Table,
+    Id,
+}
@@ -0,0 +1,170 @@
+use sea_orm_migration::{prelude::*, sea_query::extension::postgres::Type};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ReviewStatus::Table)
+                    .values([
+                        ReviewStatus::Pending,
+                        ReviewStatus::Published,
+                        ReviewStatus::Hidden,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Review::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Review::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Review::MovieId).integer().not_null())
+                    .col(ColumnDef::new(Review::AuthorId).integer().not_null())
+                    .col(
+                        ColumnDef::new(Review::Title)
+                            .string()
+                            .not_null()
+                            .check(Expr::cust("length(trim(title)) > 0")),
+                    )
+                    .col(
+                        ColumnDef::new(Review::Body)
+                            .text()
+                            .not_null()
+                            .check(Expr::cust("length(trim(body)) > 0")),
+                    )
+                    .col(ColumnDef::new(Review::BodyHtml).text().not_null())
+                    .col(ColumnDef::new(Review::Spoiler).boolean().not_null())
+                    .col(
+                        ColumnDef::new(Review::Status)
+                            .enumeration(
+                                ReviewStatus::Table,
+                                [
+                                    ReviewStatus::Pending,
+                                    ReviewStatus::Published,
+                                    ReviewStatus::Hidden,
+                                ],
+                            )
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Review::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Review::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Review::Table, Review::MovieId)
+                            .to(Movie::Table, Movie::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Review::Table, Review::AuthorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Movies show their latest published reviews, and moderators go through the oldest
+        // pending ones first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_review_movie_id_status_created_at")
+                    .table(Review::Table)
+                    .col(Review::MovieId)
+                    .col(Review::Status)
+                    .col(Review::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_review_status_created_at")
+                    .table(Review::Table)
+                    .col(Review::Status)
+                    .col(Review::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_review_author_id")
+                    .table(Review::Table)
+                    .col(Review::AuthorId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Review::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ReviewStatus::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Review {
+    Table,
+    Id,
+    MovieId,
+    AuthorId,
+    Title,
+    Body,
+    BodyHtml,
+    Spoiler,
+    Status,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+pub enum ReviewStatus {
+    Table,
+    Pending,
+    Published,
+    Hidden,
+}
+
+#[derive(DeriveIden)]
+enum Movie {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
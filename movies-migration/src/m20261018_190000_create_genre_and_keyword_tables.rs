@@ -0,0 +1,189 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Movies are found by genre or keyword through the second column of the join tables,
+        // their primary key covering lookups by movie
+        manager
+            .create_table(
+                Table::create()
+                    .table(Genre::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Genre::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Genre::Name)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                            .check(Expr::cust("length(trim(name)) > 0")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MovieGenre::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MovieGenre::MovieId).integer().not_null())
+                    .col(ColumnDef::new(MovieGenre::GenreId).integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(MovieGenre::MovieId)
+                            .col(MovieGenre::GenreId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MovieGenre::Table, MovieGenre::MovieId)
+                            .to(Movie::Table, Movie::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MovieGenre::Table, MovieGenre::GenreId)
+                            .to(Genre::Table, Genre::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_movie_genre_genre_id")
+                    .table(MovieGenre::Table)
+                    .col(MovieGenre::GenreId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Keyword::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Keyword::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Keyword::Name)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                            .check(Expr::cust("length(trim(name)) > 0")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MovieKeyword::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MovieKeyword::MovieId).integer().not_null())
+                    .col(ColumnDef::new(MovieKeyword::KeywordId).integer().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(MovieKeyword::MovieId)
+                            .col(MovieKeyword::KeywordId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MovieKeyword::Table, MovieKeyword::MovieId)
+                            .to(Movie::Table, Movie::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MovieKeyword::Table, MovieKeyword::KeywordId)
+                            .to(Keyword::Table, Keyword::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_movie_keyword_keyword_id")
+                    .table(MovieKeyword::Table)
+                    .col(MovieKeyword::KeywordId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MovieKeyword::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Keyword::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MovieGenre::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Genre::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Genre {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum MovieGenre {
+    Table,
+    MovieId,
+    GenreId,
+}
+
+#[derive(DeriveIden)]
+enum Keyword {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum MovieKeyword {
+    Table,
+    MovieId,
+    KeywordId,
+}
+
+#[derive(DeriveIden)]
+enum Movie {
+    Table,
+    Id,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Generated `tsvector` columns searched by `movies_core::search`, which must use the same text
+/// search configurations to match them.
+const SEARCH_VECTORS: [(&str, &str); 2] = [
+    (
+        "movie",
+        "setweight(to_tsvector('english', title), 'A') || \
+         setweight(to_tsvector('english', description), 'B')",
+    ),
+    ("person", "to_tsvector('simple', name)"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, vector) in SEARCH_VECTORS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE \"{table}\" ADD COLUMN search_vector tsvector \
+                 GENERATED ALWAYS AS ({vector}) STORED"
+            ))
+            .await?;
+
+            db.execute_unprepared(&format!(
+                "CREATE INDEX \"idx_{table}_search_vector\" ON \"{table}\" \
+                 USING GIN (search_vector)"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, _) in SEARCH_VECTORS.into_iter().rev() {
+            // Dropping the column drops its index as well
+            db.execute_unprepared(&format!(
+                "ALTER TABLE \"{table}\" DROP COLUMN search_vector"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
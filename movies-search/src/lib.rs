@@ -0,0 +1,464 @@
+//! Typo tolerant search of movies and persons, from an index embedded in the process.
+//!
+//! The index is a copy of the database kept up to date by listening to the changes made with
+//! `movies_core`, see [`SearchIndex::spawn_updater`]. It can be rebuilt from the database at any
+//! time with [`SearchIndex::rebuild`].
+//!
+//! [`Suggestions`] of titles and names by prefix are kept up to date the same way.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use chrono::Datelike;
+use movies_core::sea_orm::{
+    ColumnTrait, DbConn, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait,
+};
+use movies_core::{Change, ChangeListener, SearchHitType};
+use movies_entity::{movie, person};
+use serde::Serialize;
+use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query, TermQuery};
+use tantivy::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, INDEXED, STORED,
+    STRING,
+};
+use tantivy::tokenizer::{AsciiFoldingFilter, LowerCaser, SimpleTokenizer, TextAnalyzer};
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+use tracing::{error, info, instrument};
+use utoipa::ToSchema;
+
+pub use suggest::{Suggestion, Suggestions, MAX_SUGGESTIONS};
+
+mod suggest;
+
+/// Tokenizer of the text fields, splitting words and ignoring case and accents.
+const TOKENIZER: &str = "movies";
+
+/// Memory used by the index writer to buffer documents before writing them.
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+
+/// Rows loaded from the database at once while rebuilding.
+const LOAD_BATCH_SIZE: u64 = 1_000;
+
+/// Words of a query past this number are ignored, fuzzy matching each word being costly.
+const MAX_QUERY_WORDS: usize = 8;
+
+/// A movie or person matching a search of the index.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct IndexHit {
+    pub r#type: SearchHitType,
+    pub id: i32,
+    /// Title of the movie or name of the person
+    pub label: String,
+    /// Year the movie was released, absent for persons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    /// Relevance of the hit, the higher the better
+    pub score: f32,
+}
+
+#[derive(Debug)]
+pub enum SearchIndexError {
+    Index(tantivy::TantivyError),
+    Database(DbErr),
+}
+
+impl fmt::Display for SearchIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchIndexError::Index(err) => write!(f, "search index error: {err}"),
+            SearchIndexError::Database(err) => write!(f, "database error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SearchIndexError {}
+
+impl From<tantivy::TantivyError> for SearchIndexError {
+    fn from(err: tantivy::TantivyError) -> Self {
+        SearchIndexError::Index(err)
+    }
+}
+
+impl From<DbErr> for SearchIndexError {
+    fn from(err: DbErr) -> Self {
+        SearchIndexError::Database(err)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    /// `movie:{id}` or `person:{id}`, identifying the document to replace or delete
+    key: Field,
+    r#type: Field,
+    id: Field,
+    label: Field,
+    description: Field,
+    year: Field,
+}
+
+impl Fields {
+    fn schema() -> (Schema, Fields) {
+        let text = |stored| {
+            let options = TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(TOKENIZER)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            );
+            if stored {
+                options.set_stored()
+            } else {
+                options
+            }
+        };
+
+        let mut builder = Schema::builder();
+        let fields = Fields {
+            key: builder.add_text_field("key", STRING),
+            r#type: builder.add_text_field("type", STRING | STORED),
+            id: builder.add_i64_field("id", INDEXED | STORED),
+            label: builder.add_text_field("label", text(true)),
+            description: builder.add_text_field("description", text(false)),
+            year: builder.add_i64_field("year", STORED),
+        };
+
+        (builder.build(), fields)
+    }
+}
+
+/// Index of the titles and descriptions of movies and of the names of persons.
+///
+/// Searches tolerate typos and treat the last word as a prefix, to be used while typing.
+pub struct SearchIndex {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+impl SearchIndex {
+    /// Open the index stored in `dir`, creating it if needed. Only one process may open it.
+    pub fn open(dir: &Path) -> Result<Self, SearchIndexError> {
+        let (schema, fields) = Fields::schema();
+        std::fs::create_dir_all(dir).map_err(|err| {
+            tantivy::TantivyError::SystemError(format!("cannot create `{}`: {err}", dir.display()))
+        })?;
+        let directory = MmapDirectory::open(dir).map_err(tantivy::TantivyError::from)?;
+
+        SearchIndex::new(Index::open_or_create(directory, schema)?, fields)
+    }
+
+    /// Create an empty index held in memory.
+    pub fn in_memory() -> Result<Self, SearchIndexError> {
+        let (schema, fields) = Fields::schema();
+
+        SearchIndex::new(Index::create_in_ram(schema), fields)
+    }
+
+    fn new(index: Index, fields: Fields) -> Result<Self, SearchIndexError> {
+        index.tokenizers().register(TOKENIZER, analyzer());
+
+        // Reloaded after each commit, so that changes are visible as soon as they are applied
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let writer = index.writer(WRITER_MEMORY_BYTES)?;
+
+        Ok(SearchIndex {
+            index,
+            reader,
+            writer: Mutex::new(writer),
+            fields,
+        })
+    }
+
+    /// Number of movies and persons in the index.
+    pub fn len(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Apply `changes` and commit them, making them visible to searches.
+    pub fn apply(&self, changes: impl IntoIterator<Item = Change>) -> Result<(), SearchIndexError> {
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+
+        for change in changes {
+            match change {
+                Change::MovieSaved(movie) => {
+                    writer.delete_term(self.key("movie", movie.id));
+                    writer.add_document(self.movie_document(&movie))?;
+                }
+                Change::MovieDeleted(id) => {
+                    writer.delete_term(self.key("movie", id));
+                }
+                Change::PersonSaved(person) => {
+                    writer.delete_term(self.key("person", person.id));
+                    writer.add_document(self.person_document(&person))?;
+                }
+                Change::PersonDeleted(id) => {
+                    writer.delete_term(self.key("person", id));
+                }
+            }
+        }
+
+        self.commit(&mut writer)
+    }
+
+    /// Replace the content of the index with the movies and persons of the database, returning
+    /// how many were indexed.
+    ///
+    /// Searches keep seeing the previous content until the new one is committed.
+    #[instrument(skip_all)]
+    pub async fn rebuild(&self, db: &DbConn) -> Result<u64, SearchIndexError> {
+        let (movies, persons) = load_all(db).await?;
+        let documents: Vec<_> = movies
+            .iter()
+            .map(|movie| self.movie_document(movie))
+            .chain(persons.iter().map(|person| self.person_document(person)))
+            .collect();
+
+        let count = documents.len() as u64;
+        let mut writer = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+        writer.delete_all_documents()?;
+        for document in documents {
+            writer.add_document(document)?;
+        }
+        self.commit(&mut writer)?;
+
+        info!("Indexed {count} movies and persons");
+
+        Ok(count)
+    }
+
+    /// Search movie titles and descriptions and person names, best hits first.
+    ///
+    /// Every word of `query` must match, allowing one typo in words of 3 to 5 letters and two in
+    /// longer ones, the last word also matching the start of longer words.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<IndexHit>, SearchIndexError> {
+        let words = self.words(query)?;
+        let Some(last) = words.len().checked_sub(1) else {
+            return Ok(vec![]);
+        };
+
+        let clauses = words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                let query: Box<dyn Query> = Box::new(self.word_query(word, index == last));
+                (Occur::Must, query)
+            })
+            .collect();
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(
+            &BooleanQuery::new(clauses),
+            &TopDocs::with_limit(limit.max(1)),
+        )?;
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs {
+            let document: TantivyDocument = searcher.doc(address)?;
+            if let Some(hit) = self.hit(&document, score) {
+                hits.push(hit);
+            }
+        }
+
+        Ok(hits)
+    }
+
+    /// Apply the changes told to the returned listener in a background thread, committing them in
+    /// batches.
+    ///
+    /// The thread stops once the returned listener is dropped.
+    pub fn spawn_updater(self: Arc<Self>) -> Result<Arc<dyn ChangeListener>, SearchIndexError> {
+        let (sender, receiver) = mpsc::channel::<Change>();
+
+        thread::Builder::new()
+            .name("search-index".to_owned())
+            .spawn(move || {
+                while let Ok(change) = receiver.recv() {
+                    let changes = std::iter::once(change).chain(receiver.try_iter());
+                    if let Err(err) = self.apply(changes) {
+                        error!("Cannot update the search index: {err}");
+                    }
+                }
+            })
+            .map_err(|err| {
+                tantivy::TantivyError::SystemError(format!("cannot start the updater: {err}"))
+            })?;
+
+        Ok(Arc::new(IndexUpdater { sender }))
+    }
+
+    fn commit(&self, writer: &mut IndexWriter) -> Result<(), SearchIndexError> {
+        writer.commit()?;
+        self.reader.reload()?;
+
+        Ok(())
+    }
+
+    fn key(&self, r#type: &str, id: i32) -> Term {
+        Term::from_field_text(self.fields.key, &format!("{type}:{id}"))
+    }
+
+    fn movie_document(&self, movie: &movie::Model) -> TantivyDocument {
+        let fields = self.fields;
+
+        doc!(
+            fields.key => format!("movie:{}", movie.id),
+            fields.r#type => "movie",
+            fields.id => i64::from(movie.id),
+            fields.label => movie.title.as_str(),
+            fields.description => movie.description.as_str(),
+            fields.year => i64::from(movie.release_date.year()),
+        )
+    }
+
+    fn person_document(&self, person: &person::Model) -> TantivyDocument {
+        let fields = self.fields;
+
+        doc!(
+            fields.key => format!("person:{}", person.id),
+            fields.r#type => "person",
+            fields.id => i64::from(person.id),
+            fields.label => person.name.as_str(),
+        )
+    }
+
+    /// Split `query` into words the way the text fields are.
+    fn words(&self, query: &str) -> Result<Vec<String>, SearchIndexError> {
+        let mut analyzer = self.index.tokenizer_for_field(self.fields.label)?;
+        let mut stream = analyzer.token_stream(query);
+
+        let mut words = vec![];
+        while stream.advance() && words.len() < MAX_QUERY_WORDS {
+            words.push(stream.token().text.clone());
+        }
+
+        Ok(words)
+    }
+
+    /// Match `word` in labels and descriptions, exact matches and labels scoring higher.
+    fn word_query(&self, word: &str, is_last: bool) -> BooleanQuery {
+        let distance = match word.chars().count() {
+            0..=2 => 0,
+            3..=5 => 1,
+            _ => 2,
+        };
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![];
+        for (field, boost) in [(self.fields.label, 2.0), (self.fields.description, 1.0)] {
+            let term = Term::from_field_text(field, word);
+            clauses.push((
+                Occur::Should,
+                Box::new(BoostQuery::new(
+                    Box::new(TermQuery::new(term.clone(), IndexRecordOption::WithFreqs)),
+                    boost,
+                )),
+            ));
+            // Fuzzy matches all score the same, lower than most exact ones
+            clauses.push((
+                Occur::Should,
+                Box::new(BoostQuery::new(
+                    Box::new(FuzzyTermQuery::new(term.clone(), distance, true)),
+                    boost * 0.5,
+                )),
+            ));
+            if is_last {
+                clauses.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(
+                        Box::new(FuzzyTermQuery::new_prefix(term, distance, true)),
+                        boost * 0.5,
+                    )),
+                ));
+            }
+        }
+
+        BooleanQuery::new(clauses)
+    }
+
+    fn hit(&self, document: &TantivyDocument, score: f32) -> Option<IndexHit> {
+        let fields = self.fields;
+        let r#type = match document.get_first(fields.r#type)?.as_str()? {
+            "movie" => SearchHitType::Movie,
+            "person" => SearchHitType::Person,
+            _ => return None,
+        };
+
+        Some(IndexHit {
+            r#type,
+            id: document.get_first(fields.id)?.as_i64()?.try_into().ok()?,
+            label: document.get_first(fields.label)?.as_str()?.to_owned(),
+            year: document
+                .get_first(fields.year)
+                .and_then(|year| year.as_i64())
+                .and_then(|year| year.try_into().ok()),
+            score,
+        })
+    }
+}
+
+/// Splits text into words, ignoring case and accents.
+fn analyzer() -> TextAnalyzer {
+    TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(LowerCaser)
+        .filter(AsciiFoldingFilter)
+        .build()
+}
+
+/// Load every movie and person of the database.
+async fn load_all(db: &DbConn) -> Result<(Vec<movie::Model>, Vec<person::Model>), DbErr> {
+    let movies = load_by_id::<movie::Entity>(db, movie::Column::Id, |movie| movie.id).await?;
+    let persons = load_by_id::<person::Entity>(db, person::Column::Id, |person| person.id).await?;
+
+    Ok((movies, persons))
+}
+
+/// Load every row of an entity, in batches following their ids so that each one is a range scan
+/// of the primary key, whatever the number of rows before it.
+async fn load_by_id<E: EntityTrait>(
+    db: &DbConn,
+    id_column: E::Column,
+    id: impl Fn(&E::Model) -> i32,
+) -> Result<Vec<E::Model>, DbErr> {
+    let mut models = vec![];
+    let mut after = None;
+
+    loop {
+        let batch = E::find()
+            .apply_if(after, |select, after| select.filter(id_column.gt(after)))
+            .order_by_asc(id_column)
+            .limit(LOAD_BATCH_SIZE)
+            .all(db)
+            .await?;
+
+        let is_last = (batch.len() as u64) < LOAD_BATCH_SIZE;
+        after = batch.last().map(&id);
+        models.extend(batch);
+
+        if is_last {
+            return Ok(models);
+        }
+    }
+}
+
+/// Sends the changes of `movies_core` to the thread updating the index.
+struct IndexUpdater {
+    sender: mpsc::Sender<Change>,
+}
+
+impl ChangeListener for IndexUpdater {
+    fn changed(&self, change: Change) {
+        if self.sender.send(change).is_err() {
+            error!("Cannot update the search index, its updater has stopped");
+        }
+    }
+}
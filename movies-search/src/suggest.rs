@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{RwLock, RwLockWriteGuard};
+
+use chrono::Datelike;
+use movies_core::sea_orm::{DbConn, DbErr};
+use movies_core::{Change, ChangeListener, SearchHitType};
+use serde::Serialize;
+use tracing::{info, instrument};
+use utoipa::ToSchema;
+
+use crate::{analyzer, load_all};
+
+/// Most suggestions returned for a prefix, which is also how many are kept ready in each node.
+pub const MAX_SUGGESTIONS: usize = 20;
+
+/// A movie or person whose title or name starts with the prefix typed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Suggestion {
+    pub r#type: SearchHitType,
+    pub id: i32,
+    /// Title of the movie or name of the person
+    pub label: String,
+    /// Year the movie was released, absent for persons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+}
+
+/// Titles of movies and names of persons by prefix, held in memory.
+///
+/// Labels match from the start of any of their words, so `wa` suggests "Star Wars", labels
+/// matching from their first word coming first, then shorter labels.
+pub struct Suggestions {
+    movies: RwLock<Trie>,
+    persons: RwLock<Trie>,
+}
+
+impl Default for Suggestions {
+    fn default() -> Self {
+        Suggestions {
+            movies: RwLock::new(Trie::new(SearchHitType::Movie)),
+            persons: RwLock::new(Trie::new(SearchHitType::Person)),
+        }
+    }
+}
+
+impl Suggestions {
+    /// Suggest up to `limit` movies and persons of the given `types` for `prefix`, best first.
+    pub fn suggest(&self, prefix: &str, types: &[SearchHitType], limit: usize) -> Vec<Suggestion> {
+        let mut key = normalize(prefix);
+        if key.is_empty() {
+            return vec![];
+        }
+        // A finished word only matches whole words, which keys end with a space for
+        if prefix.ends_with(char::is_whitespace) {
+            key.push(' ');
+        }
+
+        let mut ranked = vec![];
+        for (r#type, trie) in [
+            (SearchHitType::Movie, &self.movies),
+            (SearchHitType::Person, &self.persons),
+        ] {
+            if types.contains(&r#type) {
+                let trie = trie.read().unwrap_or_else(|err| err.into_inner());
+                ranked.extend(trie.suggest(&key, limit));
+            }
+        }
+
+        ranked.sort_by_key(|(rank, _)| *rank);
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(_, suggestion)| suggestion)
+            .collect()
+    }
+
+    /// Replace the suggestions with the movies and persons of the database, returning how many
+    /// there are.
+    #[instrument(skip_all)]
+    pub async fn rebuild(&self, db: &DbConn) -> Result<u64, DbErr> {
+        let (movies, persons) = load_all(db).await?;
+        let count = (movies.len() + persons.len()) as u64;
+
+        let mut trie = Trie::new(SearchHitType::Movie);
+        for movie in movies {
+            trie.insert(movie.id, movie.title, Some(movie.release_date.year()));
+        }
+        *write(&self.movies) = trie;
+
+        let mut trie = Trie::new(SearchHitType::Person);
+        for person in persons {
+            trie.insert(person.id, person.name, None);
+        }
+        *write(&self.persons) = trie;
+
+        info!("Loaded {count} suggestions");
+
+        Ok(count)
+    }
+
+    /// Nodes of the prefix trees of the movies and persons, which grow with their labels.
+    pub fn node_count(&self) -> usize {
+        [&self.movies, &self.persons]
+            .into_iter()
+            .map(|trie| {
+                trie.read()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .node_count()
+            })
+            .sum()
+    }
+}
+
+impl ChangeListener for Suggestions {
+    fn changed(&self, change: Change) {
+        match change {
+            Change::MovieSaved(movie) => {
+                write(&self.movies).insert(movie.id, movie.title, Some(movie.release_date.year()))
+            }
+            Change::MovieDeleted(id) => write(&self.movies).remove(id),
+            Change::PersonSaved(person) => {
+                write(&self.persons).insert(person.id, person.name, None)
+            }
+            Change::PersonDeleted(id) => write(&self.persons).remove(id),
+        }
+    }
+}
+
+fn write(trie: &RwLock<Trie>) -> RwLockWriteGuard<'_, Trie> {
+    trie.write().unwrap_or_else(|err| err.into_inner())
+}
+
+fn normalize(text: &str) -> String {
+    let mut analyzer = analyzer();
+    let mut stream = analyzer.token_stream(text);
+
+    let mut normalized = String::new();
+    while stream.advance() {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(&stream.token().text);
+    }
+
+    normalized
+}
+
+/// Order of suggestions, the lowest first: labels matching from their first word, then shorter
+/// labels, then older ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Rank {
+    from_later_word: bool,
+    len: usize,
+    id: i32,
+}
+
+struct Entry {
+    label: String,
+    year: Option<i32>,
+    /// Normalized label from the start of each of its words, followed by a space
+    keys: Vec<String>,
+}
+
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<char, usize>,
+    /// Ranks of the labels whose keys end at this node
+    ends: Vec<Rank>,
+    /// Best ranks of the labels with keys in the subtree of this node, at most one per label
+    best: Vec<Rank>,
+}
+
+/// Prefix tree of the keys of labels, each node keeping its best suggestions ready.
+struct Trie {
+    r#type: SearchHitType,
+    /// Nodes by index, the root first
+    nodes: Vec<Node>,
+    /// Indexes of the nodes pruned from the tree, reused before adding nodes
+    free: Vec<usize>,
+    entries: HashMap<i32, Entry>,
+}
+
+impl Trie {
+    fn new(r#type: SearchHitType) -> Self {
+        Trie {
+            r#type,
+            nodes: vec![Node::default()],
+            free: vec![],
+            entries: HashMap::new(),
+        }
+    }
+
+    fn suggest(&self, key: &str, limit: usize) -> Vec<(Rank, Suggestion)> {
+        let Some(node) = self.find(key) else {
+            return vec![];
+        };
+
+        self.nodes[node]
+            .best
+            .iter()
+            .take(limit)
+            .map(|rank| {
+                let entry = &self.entries[&rank.id];
+                let suggestion = Suggestion {
+                    r#type: self.r#type,
+                    id: rank.id,
+                    label: entry.label.clone(),
+                    year: entry.year,
+                };
+                (*rank, suggestion)
+            })
+            .collect()
+    }
+
+    fn insert(&mut self, id: i32, label: String, year: Option<i32>) {
+        self.remove(id);
+
+        let normalized = normalize(&label);
+        let keys: Vec<String> = normalized
+            .char_indices()
+            .filter(|&(index, c)| index == 0 || (c != ' ' && normalized[..index].ends_with(' ')))
+            .map(|(index, _)| format!("{} ", &normalized[index..]))
+            .collect();
+
+        for (word, key) in keys.iter().enumerate() {
+            let rank = Rank {
+                from_later_word: word > 0,
+                len: label.chars().count(),
+                id,
+            };
+
+            let mut node = 0;
+            for c in key.chars() {
+                node = match self.nodes[node].children.get(&c) {
+                    Some(&child) => child,
+                    None => {
+                        let child = match self.free.pop() {
+                            Some(child) => child,
+                            None => {
+                                self.nodes.push(Node::default());
+                                self.nodes.len() - 1
+                            }
+                        };
+                        self.nodes[node].children.insert(c, child);
+                        child
+                    }
+                };
+                add_best(&mut self.nodes[node].best, rank);
+            }
+            self.nodes[node].ends.push(rank);
+        }
+
+        self.entries.insert(id, Entry { label, year, keys });
+    }
+
+    fn remove(&mut self, id: i32) {
+        let Some(entry) = self.entries.remove(&id) else {
+            return;
+        };
+
+        for key in &entry.keys {
+            let path = self.path(key);
+            if let Some(&end) = path.last() {
+                self.nodes[end].ends.retain(|rank| rank.id != id);
+            }
+
+            // From the deepest node up, as refilling a node reads the nodes below it
+            for &node in path.iter().rev() {
+                let best = &mut self.nodes[node].best;
+                let len = best.len();
+                best.retain(|rank| rank.id != id);
+                if best.len() < len && len == MAX_SUGGESTIONS {
+                    self.refill(node);
+                }
+            }
+
+            self.prune(key, &path);
+        }
+    }
+
+    /// Take away the nodes of `path` left without keys below them, from the deepest up.
+    fn prune(&mut self, key: &str, path: &[usize]) {
+        let chars: Vec<char> = key.chars().take(path.len()).collect();
+
+        for depth in (0..path.len()).rev() {
+            let node = path[depth];
+            if !self.nodes[node].children.is_empty() || !self.nodes[node].ends.is_empty() {
+                break;
+            }
+
+            let parent = if depth == 0 { 0 } else { path[depth - 1] };
+            self.nodes[parent].children.remove(&chars[depth]);
+            self.nodes[node] = Node::default();
+            self.free.push(node);
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    /// Recompute the best suggestions of `node` from its own and those of its children.
+    fn refill(&mut self, node: usize) {
+        let node_ref = &self.nodes[node];
+        let children = node_ref
+            .children
+            .values()
+            .flat_map(|&child| &self.nodes[child].best);
+
+        let mut best = vec![];
+        // Children not on the path of the key being removed may still hold its label
+        for &rank in node_ref.ends.iter().chain(children) {
+            if self.entries.contains_key(&rank.id) {
+                add_best(&mut best, rank);
+            }
+        }
+
+        self.nodes[node].best = best;
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        key.chars()
+            .try_fold(0, |node, c| self.nodes[node].children.get(&c).copied())
+    }
+
+    /// Nodes of the characters of `key`, without the root.
+    fn path(&self, key: &str) -> Vec<usize> {
+        let mut path = Vec::with_capacity(key.len());
+        let mut node = 0;
+
+        for c in key.chars() {
+            match self.nodes[node].children.get(&c) {
+                Some(&child) => node = child,
+                None => break,
+            }
+            path.push(node);
+        }
+
+        path
+    }
+}
+
+/// Add `rank` to the sorted `best` ranks, keeping the best one per label and at most
+/// `MAX_SUGGESTIONS` of them.
+fn add_best(best: &mut Vec<Rank>, rank: Rank) {
+    if let Some(index) = best.iter().position(|other| other.id == rank.id) {
+        if best[index] <= rank {
+            return;
+        }
+        best.remove(index);
+    }
+
+    let index = best.partition_point(|other| *other < rank);
+    if index < MAX_SUGGESTIONS {
+        best.insert(index, rank);
+        best.truncate(MAX_SUGGESTIONS);
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{TimeZone, Utc};
+use movies_core::{Change, SearchHitType};
+use movies_entity::{movie, person};
+use movies_search::{IndexHit, SearchIndex};
+
+fn movie(id: i32, title: &str, description: &str) -> movie::Model {
+    movie::Model {
+        id,
+        title: title.to_owned(),
+        release_date: Utc.with_ymd_and_hms(1977, 5, 25, 0, 0, 0).unwrap(),
+        poster_url: Default::default(),
+        description: description.to_owned(),
+        rating: 3,
+        user_rating_histogram: Default::default(),
+    }
+}
+
+fn found(hits: Vec<IndexHit>) -> Vec<(SearchHitType, i32)> {
+    hits.into_iter().map(|hit| (hit.r#type, hit.id)).collect()
+}
+
+#[test]
+fn search_with_typos_and_prefixes() -> Result<(), Box<dyn std::error::Error>> {
+    // arrange
+    let index = SearchIndex::in_memory()?;
+    index.apply([
+        Change::MovieSaved(movie(1, "Star Wars", "A farm boy joins the rebellion")),
+        Change::MovieSaved(movie(2, "Stardust", "A young man crosses a wall")),
+        Change::MovieSaved(movie(3, "Alien", "The crew of a space freighter")),
+        Change::PersonSaved(person::Model {
+            id: 1,
+            name: "Zoë Saldaña".to_owned(),
+        }),
+    ])?;
+
+    // act
+    let typos = index.search("Stra Wras", 10)?;
+    let prefix = index.search("star w", 10)?;
+    let exact_first = index.search("star", 10)?;
+    let accents = index.search("zoe saldana", 10)?;
+    let description = index.search("frieghter", 10)?;
+
+    // assert
+    assert_eq!(found(typos.clone()), [(SearchHitType::Movie, 1)]);
+    assert_eq!(typos[0].label, "Star Wars");
+    assert_eq!(typos[0].year, Some(1977));
+    assert_eq!(found(prefix), [(SearchHitType::Movie, 1)]);
+    assert_eq!(
+        found(exact_first),
+        [(SearchHitType::Movie, 1), (SearchHitType::Movie, 2)]
+    );
+    assert_eq!(found(accents.clone()), [(SearchHitType::Person, 1)]);
+    assert_eq!(accents[0].year, None);
+    assert_eq!(found(description), [(SearchHitType::Movie, 3)]);
+    assert!(index.search("  ", 10)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn apply_changes_incrementally() -> Result<(), Box<dyn std::error::Error>> {
+    // arrange
+    let index = SearchIndex::in_memory()?;
+    index.apply([
+        Change::MovieSaved(movie(1, "Alien", "")),
+        Change::MovieSaved(movie(2, "Heat", "")),
+    ])?;
+
+    // act
+    index.apply([
+        Change::MovieSaved(movie(1, "Aliens", "")),
+        Change::MovieDeleted(2),
+        Change::PersonSaved(person::Model {
+            id: 2,
+            name: "Michael Mann".to_owned(),
+        }),
+        Change::PersonDeleted(2),
+    ])?;
+
+    // assert
+    assert_eq!(index.len(), 1);
+    let hits = index.search("aliens", 10)?;
+    assert_eq!(found(hits.clone()), [(SearchHitType::Movie, 1)]);
+    assert_eq!(hits[0].label, "Aliens");
+    assert!(index.search("heat", 10)?.is_empty());
+    assert!(index.search("mann", 10)?.is_empty());
+
+    Ok(())
+}
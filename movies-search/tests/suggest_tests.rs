@@ -0,0 +1,119 @@
+use chrono::{TimeZone, Utc};
+use movies_core::{Change, ChangeListener, SearchHitType};
+use movies_entity::{movie, person};
+use movies_search::{Suggestion, Suggestions, MAX_SUGGESTIONS};
+
+const ALL: [SearchHitType; 2] = [SearchHitType::Movie, SearchHitType::Person];
+
+fn movie(id: i32, title: &str) -> Change {
+    Change::MovieSaved(movie::Model {
+        id,
+        title: title.to_owned(),
+        release_date: Utc.with_ymd_and_hms(1979, 5, 25, 0, 0, 0).unwrap(),
+        poster_url: Default::default(),
+        description: Default::default(),
+        rating: 3,
+        user_rating_histogram: Default::default(),
+    })
+}
+
+fn person(id: i32, name: &str) -> Change {
+    Change::PersonSaved(person::Model {
+        id,
+        name: name.to_owned(),
+    })
+}
+
+fn labels(suggestions: Vec<Suggestion>) -> Vec<String> {
+    suggestions
+        .into_iter()
+        .map(|suggestion| suggestion.label)
+        .collect()
+}
+
+#[test]
+fn suggest_labels_by_prefix() {
+    // arrange
+    let suggestions = Suggestions::default();
+    for change in [
+        movie(1, "Star Wars"),
+        movie(2, "Stardust"),
+        movie(3, "A Star Is Born"),
+        movie(4, "Alien"),
+        person(1, "Sigourney Weaver"),
+        person(2, "Álvaro Stark"),
+    ] {
+        suggestions.changed(change);
+    }
+
+    // act
+    let star = suggestions.suggest("sta", &ALL, 10);
+    let whole_word = suggestions.suggest("STAR ", &ALL, 10);
+    let last_word = suggestions.suggest("alien ", &ALL, 10);
+    let persons = suggestions.suggest("alva", &[SearchHitType::Person], 10);
+    let later_words = suggestions.suggest("star wa", &ALL, 10);
+    let limited = suggestions.suggest("s", &[SearchHitType::Movie], 1);
+
+    // assert
+    assert_eq!(
+        labels(star.clone()),
+        ["Stardust", "Star Wars", "Álvaro Stark", "A Star Is Born"]
+    );
+    assert_eq!(star[0].r#type, SearchHitType::Movie);
+    assert_eq!(star[0].year, Some(1979));
+    assert_eq!(star[2].year, None);
+    assert_eq!(labels(whole_word), ["Star Wars", "A Star Is Born"]);
+    assert_eq!(labels(last_word), ["Alien"]);
+    assert_eq!(labels(persons), ["Álvaro Stark"]);
+    assert_eq!(labels(later_words), ["Star Wars"]);
+    assert_eq!(labels(limited), ["Stardust"]);
+    assert!(suggestions.suggest(" ", &ALL, 10).is_empty());
+    assert!(suggestions.suggest("star", &[], 10).is_empty());
+}
+
+#[test]
+fn follow_renames_and_deletions() {
+    // arrange
+    let suggestions = Suggestions::default();
+    let count = MAX_SUGGESTIONS as i32 + 5;
+    for id in 1..=count {
+        suggestions.changed(movie(id, &format!("Alien {id}")));
+    }
+
+    // act
+    suggestions.changed(movie(1, "Aliens"));
+    for id in 2..=6 {
+        suggestions.changed(Change::MovieDeleted(id));
+    }
+
+    // assert
+    let aliens = suggestions.suggest("alien", &ALL, MAX_SUGGESTIONS);
+    assert_eq!(aliens.len(), MAX_SUGGESTIONS);
+    assert_eq!(aliens[0].label, "Aliens");
+    assert_eq!(aliens[1].label, "Alien 7");
+    assert_eq!(aliens[MAX_SUGGESTIONS - 1].label, format!("Alien {count}"));
+    assert_eq!(labels(suggestions.suggest("aliens", &ALL, 10)), ["Aliens"]);
+    assert!(suggestions.suggest("alien 2 ", &ALL, 10).is_empty());
+}
+
+#[test]
+fn prune_nodes_of_removed_labels() {
+    // arrange
+    let suggestions = Suggestions::default();
+    suggestions.changed(movie(1, "Alien"));
+    suggestions.changed(person(1, "Sigourney Weaver"));
+    let nodes = suggestions.node_count();
+
+    // act
+    for round in 0..100 {
+        suggestions.changed(movie(2, &format!("The Thing {round}")));
+        suggestions.changed(person(2, &format!("Kurt Russell {round}")));
+        suggestions.changed(Change::MovieDeleted(2));
+        suggestions.changed(Change::PersonDeleted(2));
+    }
+
+    // assert
+    assert_eq!(suggestions.node_count(), nodes);
+    assert_eq!(labels(suggestions.suggest("alien", &ALL, 10)), ["Alien"]);
+    assert!(suggestions.suggest("the", &ALL, 10).is_empty());
+}
@@ -1,3 +1,209 @@
-fn main() {
-    movies_api::main();
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use movies_api::api_keys::{ApiKeyCommand, ApiKeyScope};
+use movies_api::config::{
+    ConfigLayer, DatabaseLayer, LogFormat, LogLayer, MigrationMode, OidcLayer, OtelLayer,
+    SearchLayer, ServerLayer, SessionLayer,
+};
+
+/// Serve the Rust Movies API.
+///
+/// Settings are read from the config file, then the environment, then these flags, each
+/// overriding the previous ones.
+#[derive(Debug, Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// TOML config file [default: `$MOVIES_CONFIG`, or `movies.toml` if it exists]
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Host to listen on [env: HOST]
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Port to listen on [env: PORT]
+    #[arg(short, long)]
+    port: Option<u16>,
+
+    /// Seconds given to in-flight requests to complete on shutdown [env: SHUTDOWN_TIMEOUT_SECS]
+    #[arg(long)]
+    shutdown_timeout_secs: Option<u64>,
+
+    /// Postgres connection URL [env: DATABASE_URL]
+    #[arg(long)]
+    database_url: Option<String>,
+
+    /// Maximum number of database connections [env: DATABASE_MAX_CONNECTIONS]
+    #[arg(long)]
+    max_connections: Option<u32>,
+
+    /// Minimum number of idle database connections [env: DATABASE_MIN_CONNECTIONS]
+    #[arg(long)]
+    min_connections: Option<u32>,
+
+    /// Seconds to wait when opening a database connection [env: DATABASE_CONNECT_TIMEOUT_SECS]
+    #[arg(long)]
+    connect_timeout_secs: Option<u64>,
+
+    /// Seconds to wait for a free database connection [env: DATABASE_ACQUIRE_TIMEOUT_SECS]
+    #[arg(long)]
+    acquire_timeout_secs: Option<u64>,
+
+    /// Seconds before closing an idle database connection [env: DATABASE_IDLE_TIMEOUT_SECS]
+    #[arg(long)]
+    idle_timeout_secs: Option<u64>,
+
+    /// Format of the logs, `text` or `json` [env: LOG_FORMAT]
+    #[arg(long)]
+    log_format: Option<LogFormat>,
+
+    /// OTLP/HTTP endpoint to export traces to, with the `otel` feature
+    /// [env: OTEL_EXPORTER_OTLP_ENDPOINT]
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+
+    /// Share of the traces to export, from 0 to 1 [env: OTEL_TRACES_SAMPLER_ARG]
+    #[arg(long)]
+    trace_sampling_ratio: Option<f64>,
+
+    /// Seconds users stay logged in [env: SESSION_TTL_SECS]
+    #[arg(long)]
+    session_ttl_secs: Option<u64>,
+
+    /// Whether the session cookie is only sent over HTTPS, `true` or `false`
+    /// [env: SESSION_SECURE_COOKIE]
+    #[arg(long)]
+    session_secure_cookie: Option<bool>,
+
+    /// Issuer of the OpenID Connect provider to log in with [env: OIDC_ISSUER_URL]
+    #[arg(long)]
+    oidc_issuer_url: Option<String>,
+
+    /// Client id registered with the OpenID Connect provider [env: OIDC_CLIENT_ID]
+    #[arg(long)]
+    oidc_client_id: Option<String>,
+
+    /// URL of `/auth/oidc/callback` as reached by browsers [env: OIDC_REDIRECT_URL]
+    #[arg(long)]
+    oidc_redirect_url: Option<String>,
+
+    /// Directory of the search index, held in memory if unset [env: SEARCH_INDEX_DIR]
+    #[arg(long)]
+    search_index_dir: Option<PathBuf>,
+
+    /// What to do with pending migrations on startup, `apply`, `check` or `skip`
+    /// [env: MIGRATIONS]
+    #[arg(long)]
+    migrations: Option<MigrationMode>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Serve the API, which is the default
+    Serve,
+    /// Manage the API keys needed to change data through the API
+    #[command(subcommand)]
+    ApiKey(ApiKeyCli),
+    /// Manage the search index, which needs `search.index_dir` to be set
+    #[command(subcommand)]
+    SearchIndex(SearchIndexCli),
+}
+
+#[derive(Debug, Subcommand)]
+enum ApiKeyCli {
+    /// Create a key and print it, which is the only time it is shown
+    Mint {
+        /// What the key is for, to tell keys apart
+        #[arg(long)]
+        name: String,
+        /// What the key may do, `read`, `write` or `admin` making its callers viewers, editors or
+        /// admins
+        #[arg(long, default_value = "read")]
+        scope: ApiKeyScope,
+    },
+    /// Stop accepting a key
+    Revoke {
+        /// Id of the key, as printed by `list`
+        id: i32,
+    },
+    /// Print the keys, which are identified by their prefix
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum SearchIndexCli {
+    /// Index every movie and person of the database again, while the server is stopped
+    Rebuild,
+}
+
+impl From<Command> for movies_api::Command {
+    fn from(command: Command) -> Self {
+        match command {
+            Command::Serve => movies_api::Command::Serve,
+            Command::ApiKey(ApiKeyCli::Mint { name, scope }) => {
+                movies_api::Command::ApiKey(ApiKeyCommand::Mint { name, scope })
+            }
+            Command::ApiKey(ApiKeyCli::Revoke { id }) => {
+                movies_api::Command::ApiKey(ApiKeyCommand::Revoke { id })
+            }
+            Command::ApiKey(ApiKeyCli::List) => movies_api::Command::ApiKey(ApiKeyCommand::List),
+            Command::SearchIndex(SearchIndexCli::Rebuild) => {
+                movies_api::Command::RebuildSearchIndex
+            }
+        }
+    }
+}
+
+impl Cli {
+    fn into_config_layer(self) -> ConfigLayer {
+        ConfigLayer {
+            server: ServerLayer {
+                host: self.host,
+                port: self.port,
+                shutdown_timeout_secs: self.shutdown_timeout_secs,
+            },
+            database: DatabaseLayer {
+                url: self.database_url,
+                max_connections: self.max_connections,
+                min_connections: self.min_connections,
+                connect_timeout_secs: self.connect_timeout_secs,
+                acquire_timeout_secs: self.acquire_timeout_secs,
+                idle_timeout_secs: self.idle_timeout_secs,
+            },
+            log: LogLayer {
+                format: self.log_format,
+            },
+            otel: OtelLayer {
+                endpoint: self.otlp_endpoint,
+                sampling_ratio: self.trace_sampling_ratio,
+            },
+            session: SessionLayer {
+                ttl_secs: self.session_ttl_secs,
+                secure_cookie: self.session_secure_cookie,
+            },
+            oidc: OidcLayer {
+                issuer_url: self.oidc_issuer_url,
+                client_id: self.oidc_client_id,
+                redirect_url: self.oidc_redirect_url,
+                ..Default::default()
+            },
+            search: SearchLayer {
+                index_dir: self.search_index_dir,
+            },
+            ratings: Default::default(),
+            migrations: self.migrations,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = Cli::parse();
+    let config_file = args.config.take();
+    let command = args.command.take().unwrap_or(Command::Serve);
+
+    movies_api::main(config_file, args.into_config_layer(), command.into())
 }